@@ -1,76 +1,40 @@
-use crate::{base, clu_errors::CluErrors};
+use crate::base::{self, SortBy};
 use std::path::PathBuf;
 
 #[test]
-fn get_bytes_test(){
-    let number: u8 = 3;
-    assert_eq!(&[3], base::get_bytes(&number));
-    let bools: [bool;3] = [true, false, false];
-    assert_eq!(&[1,0,0], base::get_bytes(&bools));
-}
-
-#[test]
-fn parse_path_test(){
-    // Well parsed test
+fn sort_paths_test(){
     let mut pathbuf = PathBuf::new();
     pathbuf.push(".");
     pathbuf.push("tests");
     pathbuf.push("base_files");
-    pathbuf.push("*");
-    // Without hidden items
-    let result_without_hidden = base::parse_path(&pathbuf.to_str().unwrap().to_string(), false, false).unwrap();
-    assert_eq!(1, result_without_hidden.len()); // It contains sample_text.txt
-
-    // With hidden items
-    let result_with_hidden = base::parse_path(&pathbuf.to_str().unwrap().to_string(), false, true).unwrap();
-    assert_eq!(3, result_with_hidden.len()); // It contains the same as the previous + .hidden_folder + .hidden_text.txt
-
-    // Invalid path due to invalid parent dir test
-    assert_eq!(CluErrors::InputError("The introduced path: '' isn't valid.".to_string()), base::parse_path(&"".to_string(),false,false).unwrap_err());
-
-    // Invalid regex in the search path
-    let mut pathbuf = PathBuf::new();
-    pathbuf.push(".");
-    pathbuf.push("[a-s+");
-    assert_eq!(CluErrors::RegexError("[a-s+".to_string()), base::parse_path(&pathbuf.to_str().unwrap().to_string(),false,false).unwrap_err());
-
-    // Invalid file name in the search path
-    let mut pathbuf = PathBuf::new();
-    pathbuf.push(".");
-    pathbuf.push("..");
-    assert_eq!(CluErrors::InputError(format!("The introduced path: '{}' isn't valid.", pathbuf.to_str().unwrap())), base::parse_path(&pathbuf.to_str().unwrap().to_string(), false, false).unwrap_err());
-
-    // Unable to read directory test. This happens if the user doesn't have permission to read the directory, or if it's introduced a regex that passes the filter but whose parent dir doesn't exist. We will use this second case to carry out the test
-    let mut pathbuf = PathBuf::new();
-    pathbuf.push(".");
-    pathbuf.push("sc");
-    pathbuf.push("*");
-    assert_eq!(CluErrors::UnableToReadDirectory, base::parse_path(&pathbuf.to_str().unwrap().to_string(), false, false).unwrap_err());
-
+    let mut sample_text = pathbuf.clone();
+    sample_text.push("sample_text.txt");
+    let mut hidden_text = pathbuf.clone();
+    hidden_text.push(".hidden_text.txt");
+    let paths = vec![sample_text.to_str().unwrap().to_string(), "-".to_string(), hidden_text.to_str().unwrap().to_string()];
+
+    // None keeps the traversal order
+    let mut sorted = paths.clone();
+    base::sort_paths(&mut sorted, SortBy::None);
+    assert_eq!(paths, sorted);
+
+    // Path sorts them alphabetically
+    let mut sorted = paths.clone();
+    base::sort_paths(&mut sorted, SortBy::Path);
+    assert_eq!(vec!["-".to_string(), hidden_text.to_str().unwrap().to_string(), sample_text.to_str().unwrap().to_string()], sorted);
+
+    // Mtime places first the items without modification time. We cannot ensure the modification time of the test files, but the result must be coherent with their metadata
+    let mut sorted = paths.clone();
+    base::sort_paths(&mut sorted, SortBy::Mtime);
+    assert_eq!("-", sorted[0]);
+    let modified = |path: &String| std::fs::metadata(path).unwrap().modified().unwrap();
+    assert!(modified(&sorted[1]) <= modified(&sorted[2]));
 }
 
 #[test]
-fn parse_path_recursively_test(){
-    // Base case test, the path only contains files
-    let mut pathbuf = PathBuf::new();
-    pathbuf.push(".");
-    pathbuf.push("tests");
-    pathbuf.push("base_files");
-    pathbuf.push("sample_text.txt");
-    let query = base::parse_path_recursively(&vec![pathbuf.to_str().unwrap().to_string()], false).unwrap();
-    assert_eq!(1, query.len()); 
-
-    // Recursive case test, finding all the files in the directory tree
-    let mut pathbuf = PathBuf::new();
-    pathbuf.push(".");
-    pathbuf.push("tests");
-    pathbuf.push("base_files");
-    let without_hidden_files = base::parse_path_recursively(&vec![pathbuf.to_str().unwrap().to_string()], false).unwrap();
-    let with_hidden_files = base::parse_path_recursively(&vec![pathbuf.to_str().unwrap().to_string()], true).unwrap();
-    assert_eq!(1, without_hidden_files.len()); 
-    assert_eq!(3, with_hidden_files.len()); 
-
-    // The errors that may occur here are:
-    // - Errors propagated from parse_path (already tested).
-    // - Concurrency errors. There's no way to create a unit test of that as the concurrency is defined in the function so we cannot close the channel or panick a thread from here
+fn json_test(){
+    assert_eq!("\"grep\"", base::json_string("grep"));
+    assert_eq!("\"a \\\"quoted\\\" \\\\ path\\n\\t\\u0001\"", base::json_string("a \"quoted\" \\ path\n\t\u{1}"));
+    assert_eq!("\"ñ\"", base::json_string("ñ"));
+    assert_eq!("{\"text\":\"I'm grep \u{FFFD}\"}", base::json_text(b"I'm grep \xff"));
 }
\ No newline at end of file
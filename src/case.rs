@@ -0,0 +1,58 @@
+use clap::{Args, ValueEnum};
+use regex::bytes::RegexBuilder;
+
+#[derive(Args, Clone, Copy)]
+pub struct Case{
+    /// Set this flag on to make the search pattern case insensitive.
+    #[arg(short, long, overrides_with_all = ["smart_case", "no_ignore_case"])]
+    pub ignore_case: bool,
+
+    /// Set this flag on to make the search pattern case insensitive only if it doesn't contain any uppercase letter. The letters escaped with \ (eg, \S or \W) aren't taken into account.
+    #[arg(short = 'S', long, overrides_with_all = ["ignore_case", "no_ignore_case"])]
+    pub smart_case: bool,
+
+    /// Set this flag on to make the search pattern case sensitive, which is the default. As with -i and -S, the last one of them given wins, so it can override an alias.
+    #[arg(long, overrides_with_all = ["ignore_case", "smart_case"])]
+    pub no_ignore_case: bool,
+
+    /// Set how the regular expressions treat the characters. With unicode, the case of every Unicode letter is folded (eg, É matches é) and classes such as \w, \d or \s match any Unicode character of their kind. With ascii, only the ASCII letters are folded and the classes only match ASCII characters, which is faster. Non ASCII characters can still be written in the patterns anyway.
+    #[arg(long, value_enum, value_name = "FOLDING", default_value_t = Folding::Unicode)]
+    pub case_folding: Folding
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+pub enum Folding{
+    Unicode,
+    Ascii
+}
+
+impl Case{
+    // Checks if the patterns must be matched ignoring the case. With smart case, a single uppercase letter in any pattern makes the search case sensitive
+    pub fn is_insensitive(&self, patterns: &[String]) -> bool{
+        self.ignore_case || (self.smart_case && !patterns.iter().any(|pattern| Self::has_uppercase(pattern)))
+    }
+
+    // Checks if a pattern has an uppercase letter, skipping the ones escaped with \ as they're classes or anchors instead of letters
+    fn has_uppercase(pattern: &str) -> bool{
+        let mut chars = pattern.chars();
+        while let Some(character) = chars.next(){
+            if character == '\\'{
+                chars.next();
+            }
+            else if character.is_uppercase(){
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// Starts building the regex of a pattern, so every regex of grep and find folds the case in the same way
+pub fn builder(pattern: &str, insensitive: bool, folding: Folding) -> RegexBuilder{
+    let mut builder = RegexBuilder::new(pattern);
+    builder.case_insensitive(insensitive).unicode(folding == Folding::Unicode);
+    builder
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
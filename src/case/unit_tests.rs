@@ -0,0 +1,38 @@
+use crate::case::{self, Case, Folding};
+
+// The case options given without any flag
+fn sensitive() -> Case{
+    Case{ ignore_case: false, smart_case: false, no_ignore_case: false, case_folding: Folding::Unicode }
+}
+
+#[test]
+fn is_insensitive_test(){
+    let patterns = |patterns: &[&str]| patterns.iter().map(|pattern| pattern.to_string()).collect::<Vec<String>>();
+    assert!(!sensitive().is_insensitive(&patterns(&["grep"])));
+    assert!(Case{ ignore_case: true, ..sensitive() }.is_insensitive(&patterns(&["Grep"])));
+    assert!(!Case{ no_ignore_case: true, ..sensitive() }.is_insensitive(&patterns(&["grep"])));
+
+    // With smart case, an uppercase letter in any pattern makes the search case sensitive, unless it's escaped
+    let smart = Case{ smart_case: true, ..sensitive() };
+    assert!(smart.is_insensitive(&patterns(&["grep", "error"])));
+    assert!(!smart.is_insensitive(&patterns(&["grep", "Error"])));
+    assert!(!smart.is_insensitive(&patterns(&["ÉCOLE"])));
+    assert!(smart.is_insensitive(&patterns(&[r"grep\S+\W"])));
+    assert!(smart.is_insensitive(&[]));
+}
+
+#[test]
+fn builder_test(){
+    // Unicode folding
+    let reg = case::builder("école", true, Folding::Unicode).build().unwrap();
+    assert!(reg.is_match("ÉCOLE".as_bytes()));
+    assert!(case::builder(r"^\w+$", false, Folding::Unicode).build().unwrap().is_match("école".as_bytes()));
+    assert!(!case::builder("école", false, Folding::Unicode).build().unwrap().is_match("ÉCOLE".as_bytes()));
+
+    // ASCII folding: the non ASCII letters are matched as they're written
+    let reg = case::builder("école", true, Folding::Ascii).build().unwrap();
+    assert!(reg.is_match("éCOLE".as_bytes()));
+    assert!(!reg.is_match("ÉCOLE".as_bytes()));
+    assert!(!case::builder(r"^\w+$", false, Folding::Ascii).build().unwrap().is_match("école".as_bytes()));
+    assert!(case::builder("^.$", false, Folding::Ascii).build().unwrap().is_match(b"\xff"));
+}
\ No newline at end of file
@@ -1,11 +1,22 @@
+use std::io;
+
+/// Exit status of an execution that produced the requested output.
+pub const EXIT_SUCCESS: u8 = 0;
+/// Exit status of an execution that didn't produce any output, eg: grep didn't find the pattern.
+pub const EXIT_NOT_FOUND: u8 = 1;
+/// Exit status of an execution that failed, or that produced some output but found errors along the way.
+pub const EXIT_ERROR: u8 = 2;
+
 #[derive(Debug, PartialEq)]
 pub enum CluErrors{
     MissingCommand,
-    InvalidCommandCombination(String),
+    UsageError(String),
     InputError(String),
-    UnableToReadDirectory,
+    UnableToReadDirectory(String),
     NotFoundError,
     RegexError(String),
+    OutputError(String),
+    BrokenPipe,
     UnexpectedError
 }
 
@@ -13,12 +24,35 @@ impl std::fmt::Display for CluErrors{
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self{
             Self::MissingCommand => write!(f, "Missing utility command. For more information try --help."),
-            Self::InvalidCommandCombination(mode) => write!(f,"Introduced an invalid combination of commands in {mode} mode. For more information try --help."),
+            Self::UsageError(message) => write!(f, "{message}"),
             Self::InputError(reason) => write!(f, "Input error. {reason}."),
-            Self::UnableToReadDirectory => write!(f, "We've encountered a problem reading one of the provided directories, please ensure that the path is correct and that lecture permissions are enabled."),
+            Self::UnableToReadDirectory(dir) => write!(f, "We've encountered a problem reading the directory {dir}, please ensure that the path is correct and that lecture permissions are enabled."),
             Self::NotFoundError => write!(f, "The request didn't produce any output."),
             Self::RegexError(regex) => write!(f, "The introduced regex {regex} isn't valid."),
+            Self::OutputError(reason) => write!(f, "The output cannot be written: {reason}."),
+            Self::BrokenPipe => write!(f, "The output was closed before the execution finished."),
             Self::UnexpectedError => write!(f, "The execution stopped due to an unexpected error.")
         }
     }
-}
\ No newline at end of file
+}
+
+impl CluErrors{
+    /// Exit status of the program when the execution ends with this error. NotFoundError isn't a failure but an empty research, so it's mapped to EXIT_NOT_FOUND. Every other error is mapped to EXIT_ERROR.
+    pub fn exit_code(&self) -> u8{
+        match self{
+            Self::NotFoundError => EXIT_NOT_FOUND,
+            _ => EXIT_ERROR
+        }
+    }
+
+    /// Error found while writing the output. BrokenPipe means that the reader went away (eg, the output was piped into head, which already has what it needs), so it isn't really a failure.
+    pub fn output(error: io::Error) -> CluErrors{
+        match error.kind(){
+            io::ErrorKind::BrokenPipe => Self::BrokenPipe,
+            _ => Self::OutputError(error.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
@@ -0,0 +1,23 @@
+use std::io;
+use crate::clu_errors::{CluErrors, EXIT_NOT_FOUND, EXIT_ERROR};
+
+#[test]
+fn exit_code_test(){
+    // An empty research isn't a failure
+    assert_eq!(EXIT_NOT_FOUND, CluErrors::NotFoundError.exit_code());
+    // Everything else is
+    assert_eq!(EXIT_ERROR, CluErrors::MissingCommand.exit_code());
+    assert_eq!(EXIT_ERROR, CluErrors::UsageError("error: unexpected argument '--bogus' found".to_string()).exit_code());
+    assert_eq!(EXIT_ERROR, CluErrors::InputError("foo cannot be read".to_string()).exit_code());
+    assert_eq!(EXIT_ERROR, CluErrors::UnableToReadDirectory("foo".to_string()).exit_code());
+    assert_eq!(EXIT_ERROR, CluErrors::RegexError("[a-z".to_string()).exit_code());
+    assert_eq!(EXIT_ERROR, CluErrors::UnexpectedError.exit_code());
+    assert_eq!(EXIT_ERROR, CluErrors::OutputError("disk full".to_string()).exit_code());
+}
+
+#[test]
+fn output_test(){
+    // A closed pipe is told apart from the rest of the errors writing the output
+    assert_eq!(CluErrors::BrokenPipe, CluErrors::output(io::Error::from(io::ErrorKind::BrokenPipe)));
+    assert_eq!(CluErrors::OutputError("disk full".to_string()), CluErrors::output(io::Error::other("disk full")));
+}
\ No newline at end of file
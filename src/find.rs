@@ -1,8 +1,12 @@
 use clap::Args;
-use regex::RegexBuilder;
+use regex::bytes::Regex;
+use std::{io::{self, IsTerminal, Write}, slice, time::SystemTime};
 
 mod options;
-use crate::{base, clu_errors::CluErrors, find::options::Options};
+mod info;
+mod actions;
+pub mod expression;
+use crate::{Execution, base, case::{self, Case}, clu_errors::CluErrors, pool, walker::{Walker, Traversal, Entry}, find::{options::Options, expression::{Expression, Candidate}, actions::{Action, Output}}};
 
 #[derive(Args)]
 pub struct Find{
@@ -12,40 +16,116 @@ pub struct Find{
 
     /// Specify what you're looking for using a Regex. If empty, find we'll look for all the files in the directory tree. Note: It's better to wrap this argument with quotes, otherwise your shell may reject it. Example: If you write *.txt, your shell may try to find something called *.txt in your current directory and reject the expression directly, then it's better to use find . '*.txt'
     #[arg(short, long)]
-    expression: Option<Box<String>>, //It must be a Vec<String> in order to accept automatically globbed paths (if globbing takes place)
-
-    /// Set this flag on to make the search expression case insensitive.
-    #[arg(short, long)]
-    ignore_case: bool,
+    expression: Option<String>, //It must be a Vec<String> in order to accept automatically globbed paths (if globbing takes place)
 
     /// By default, find ignores the hidden files and directories (those starting with .). Set this flag on to explicitly include them in the search. Note: If you're looking for hidden files with a determined extension, adding to the path *.txt -H may not be enough if your shell carries out globbing automatically. If that's the case, wrap the pattern into single quotes to ensure the globbing is carried out by the utility instead of by the shell.
     #[arg(short = 'H', long)]
     hidden_items:bool,
 
+    /// Set the maximum number of threads used to explore the directories. By default, one per available core.
+    #[arg(short = 'j', long, default_value_t = pool::default_threads())]
+    threads: usize,
+
+    /// Set this flag on to suppress the error messages about directories that cannot be read. Find doesn't stop if it finds one of them, it reports the error and keeps searching in the rest of the tree. Note that the exit status still reflects that an error occurred.
+    #[arg(short = 's', long)]
+    no_messages: bool,
+
+    /// Set this flag on to let -delete remove the items found without asking for confirmation. Without it, find asks before deleting anything, and refuses to delete if it cannot ask because the standard input isn't a terminal.
+    #[arg(short = 'y', long)]
+    yes: bool,
+
+    /// Set this flag on to print every item found by -print (explicit or not) as a JSON Lines object with its path, depth, type (file, dir or symlink), size in bytes and modification time since the Unix epoch, instead of just its path. The rest of the actions aren't affected.
+    #[arg(long)]
+    json: bool,
+
+    #[command(flatten)]
+    case: Case,
+
+    #[command(flatten)]
+    traversal: Traversal,
+
     #[command(flatten)]
     options: Options
 }
 
 impl Find{
 
-    pub fn execute(self) -> Result<String, CluErrors>{        
-        // Find the requested files
-        let parsed = base::parse_path_recursively(&self.from, self.hidden_items)?;
+    pub fn execute(self, out: &mut dyn Write) -> Result<Execution, CluErrors>{        
+        // The expression is checked before walking, so a mistake in it is reported straight away
+        let (expression, globals) = Expression::parse(&self.options.predicates, self.case)?;
+        let deletes = expression.any_action(&|action| matches!(action, Action::Delete));
+        if deletes && !self.yes && !io::stdin().is_terminal(){
+            return Err(CluErrors::InputError("-delete removes the items for good, so it must be confirmed. Add --yes to delete them without being asked".to_string()));
+        }
 
-        let filter = self.filter_by_name(parsed)?;
+        // Find the requested items. The directories that cannot be read are collected and reported at the end, without stopping the search
+        let mut errors = Vec::new();
+        // The global options of the expression are added to the ones given as flags
+        let mut traversal = self.traversal;
+        traversal.max_depth = traversal.max_depth.into_iter().chain(globals.max_depth).min();
+        traversal.one_file_system |= globals.xdev;
+        let walker = Walker{ hidden_items: self.hidden_items, traversal, threads: self.threads };
+        // Like in GNU find, -depth (which -delete implies, as the contents of a directory must be deleted before the directory itself) makes -prune useless
+        let contents_first = globals.contents_first || deletes;
+        let prunes = !contents_first && expression.any_action(&|action| matches!(action, Action::Prune));
+        let now = SystemTime::now(); // The times are compared to the moment find started, as in GNU find
+        let regex = self.name_regex()?;
 
-        let found = filter
-            .join("\n")
-            .trim_end_matches("\n")
-            .trim_start_matches("\n")
-            .to_string();
-        if found.is_empty(){ return Err(CluErrors::NotFoundError);}
+        // Every item is evaluated and the text produced by the actions is written as soon as the walk gives it, so it's shown while find goes on
+        let mut output = Output::new(now);
+        output.json = self.json;
+        let mut found = 0;
+        let mut evaluate = |entry: Entry| -> Result<(), CluErrors>{
+            if expression.evaluate(&Candidate::new(&entry), &mut output){
+                found += 1;
+            }
+            output.write_to(out)
+        };
+        let mut pending = Vec::new(); // With -depth, the directories waiting for their contents
+        let mut written = Ok(());
+        walker.walk_pruning(&self.from, &mut errors, |entry| prunes && expression.prunes(&Candidate::new(entry), now), |entry|{
+            if entry.depth < globals.min_depth || !regex.is_match(entry.path.as_bytes()){
+                return true;
+            }
+            let ready = if contents_first{ Self::contents_first(&mut pending, entry) } else{ vec![entry] };
+            written = ready.into_iter().try_for_each(&mut evaluate);
+            written.is_ok() // If the output cannot be written anymore, there's no point in going on
+        })?;
+        written?;
+        pending.into_iter().rev().try_for_each(&mut evaluate)?;
+        output.finish();
+        output.write_to(out)?;
+        if !output.deletions.is_empty() && (self.yes || Self::confirm_deletion(output.deletions.len())){
+            output.delete();
+        }
+        errors.append(&mut output.errors);
+
+        base::report_errors(&errors, self.no_messages);
+        if found == 0 && errors.is_empty(){ return Err(CluErrors::NotFoundError);}
         Ok(
-            found
+            Execution{ errors_occurred: !errors.is_empty() }
         )
     }
 
-    fn filter_by_name(&self, parsed: Vec<String>) -> Result<Vec<String>, CluErrors>{
+    // Reorders the entries, given one by one in the traversal order, so the contents of every directory go before the directory itself. The entry being visited and its ancestors wait in pending for their contents, and the entries that are ready are returned. Once the walk ends, the ones left in pending go in reverse order
+    fn contents_first(pending: &mut Vec<Entry>, entry: Entry) -> Vec<Entry>{
+        let mut ready = Vec::new();
+        while pending.last().is_some_and(|last| last.depth >= entry.depth){
+            ready.extend(pending.pop());
+        }
+        pending.push(entry);
+        ready
+    }
+
+    // Asks the user whether the items found by -delete must be deleted
+    fn confirm_deletion(items: usize) -> bool{
+        eprint!("Delete {} items for good? [y/N] ", items);
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer).is_ok() && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+    }
+
+    // Builds the regex of -e, which the paths of the items found must match
+    fn name_regex(&self) -> Result<Regex, CluErrors>{
         // Extract the expression into a String depending on it's shape
         let expression = match &self.expression{
             Some(expression) => {
@@ -60,20 +140,10 @@ impl Find{
         };
 
         // Build the Regex
-        let reg = RegexBuilder::new(&expression)
-            .case_insensitive(self.ignore_case)
+        let insensitive = self.case.is_insensitive(slice::from_ref(&expression));
+        case::builder(&expression, insensitive, self.case.case_folding)
             .build()
-            .map_err(|_err| CluErrors::RegexError(expression))?;
-
-        Ok(
-            parsed
-                .iter()
-                .filter_map(|item|{
-                    if reg.is_match(item){ Some(item.to_string()) }
-                    else{ None }
-                })
-                .collect::<Vec<String>>()
-        )
+            .map_err(|_err| CluErrors::RegexError(expression))
     }
 
 }
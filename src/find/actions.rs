@@ -0,0 +1,315 @@
+use std::{fs, mem, io::Write, path::Path, process::{Command, Stdio}, time::{SystemTime, UNIX_EPOCH}};
+use crate::{base, clu_errors::CluErrors, walker::Entry, find::{info, expression::Candidate}};
+
+// How many bytes of paths are given at most to a single execution of -exec ... +, to stay far from the limits of the operating system
+const BATCH_LIMIT: usize = 128 * 1024;
+
+// What find does with the items satisfying the expression. Like in GNU find, the actions are part of the expression, so their result is taken into account: -exec is true if the command succeeds, the rest of them are always true. -prune doesn't do anything here, as the walker already skipped the contents of the directories it's reached for
+#[derive(Debug)]
+pub enum Action{
+    Prune,
+    Print,
+    Print0,
+    Printf(Vec<Directive>),
+    Exec(Vec<String>), // The command and its arguments, where every {} is replaced by the path
+    ExecBatch(usize, Vec<String>), // The command and its arguments, to which the paths are added in batches. The number identifies the batch
+    Delete,
+    Ls
+}
+
+// A piece of a -printf format
+#[derive(Debug, PartialEq)]
+pub enum Directive{
+    Literal(String),
+    Path, // %p
+    Name, // %f
+    Dir, // %h
+    Size, // %s
+    Depth, // %d
+    Mode, // %m
+    Permissions, // %M
+    Mtime, // %t
+    MtimeSeconds, // %T@
+    User, // %u
+    Group, // %g
+    Uid, // %U
+    Gid, // %G
+    Type, // %y
+    Link // %l
+}
+
+// The paths waiting to be given to a command of -exec ... +
+struct Batch{
+    command: Vec<String>,
+    paths: Vec<String>,
+    length: usize
+}
+
+// Everything the actions produce while find evaluates the items: the text waiting to be written, the batches of -exec ... + still pending, the items to be deleted once confirmed and the errors found. With json, -print describes every item as a JSON object instead of just writing its path
+pub struct Output{
+    pub now: SystemTime,
+    pub json: bool,
+    pub text: String,
+    pub deletions: Vec<Entry>,
+    pub errors: Vec<CluErrors>,
+    batches: Vec<Option<Batch>>
+}
+
+impl Directive{
+    // Parses a -printf format into its directives. The escapes \n, \t, \r, \0 and \\ are also supported
+    pub fn parse(format: &str) -> Result<Vec<Directive>, CluErrors>{
+        let invalid = || CluErrors::InputError(format!("Invalid directive in the -printf format '{}'", format));
+        let mut directives = Vec::new();
+        let mut literal = String::new();
+        let mut chars = format.chars();
+        while let Some(character) = chars.next(){
+            let directive = match character{
+                '\\' => {
+                    literal.push(match chars.next(){
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('0') => '\0',
+                        Some('\\') => '\\',
+                        _ => return Err(invalid())
+                    });
+                    continue;
+                },
+                '%' => match chars.next(){
+                    Some('%') => {
+                        literal.push('%');
+                        continue;
+                    },
+                    Some('p') => Directive::Path,
+                    Some('f') => Directive::Name,
+                    Some('h') => Directive::Dir,
+                    Some('s') => Directive::Size,
+                    Some('d') => Directive::Depth,
+                    Some('m') => Directive::Mode,
+                    Some('M') => Directive::Permissions,
+                    Some('t') => Directive::Mtime,
+                    Some('T') if chars.next() == Some('@') => Directive::MtimeSeconds,
+                    Some('u') => Directive::User,
+                    Some('g') => Directive::Group,
+                    Some('U') => Directive::Uid,
+                    Some('G') => Directive::Gid,
+                    Some('y') => Directive::Type,
+                    Some('l') => Directive::Link,
+                    _ => return Err(invalid())
+                },
+                character => {
+                    literal.push(character);
+                    continue;
+                }
+            };
+            if !literal.is_empty(){
+                directives.push(Directive::Literal(mem::take(&mut literal)));
+            }
+            directives.push(directive);
+        }
+        if !literal.is_empty(){
+            directives.push(Directive::Literal(literal));
+        }
+        Ok(directives)
+    }
+
+    fn write(&self, candidate: &Candidate, text: &mut String){
+        let entry = candidate.entry;
+        let metadata = candidate.metadata();
+        let owner = metadata.and_then(info::owner);
+        let modified = metadata.and_then(|metadata| metadata.modified().ok()).unwrap_or(UNIX_EPOCH);
+        match self{
+            Self::Literal(literal) => text.push_str(literal),
+            Self::Path => text.push_str(&entry.path),
+            Self::Name => text.push_str(candidate.name()),
+            Self::Dir => text.push_str(Path::new(&entry.path).parent().and_then(|parent| parent.to_str()).filter(|parent| !parent.is_empty()).unwrap_or(".")),
+            Self::Size => text.push_str(&metadata.map_or(0, |metadata| metadata.len()).to_string()),
+            Self::Depth => text.push_str(&entry.depth.to_string()),
+            Self::Mode => text.push_str(&format!("{:o}", metadata.map_or(0, info::permissions))),
+            Self::Permissions => text.push_str(&info::symbolic_permissions(entry.file_type, metadata.map_or(0, info::permissions))),
+            Self::Mtime => text.push_str(&info::ctime(modified)),
+            Self::MtimeSeconds => {
+                let since_epoch = modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+                text.push_str(&format!("{}.{:09}", since_epoch.as_secs(), since_epoch.subsec_nanos()));
+            },
+            Self::User => text.push_str(&owner.map_or(String::new(), |(uid, _gid)| info::lookup_name(info::USERS, uid))),
+            Self::Group => text.push_str(&owner.map_or(String::new(), |(_uid, gid)| info::lookup_name(info::GROUPS, gid))),
+            Self::Uid => text.push_str(&owner.map_or(String::new(), |(uid, _gid)| uid.to_string())),
+            Self::Gid => text.push_str(&owner.map_or(String::new(), |(_uid, gid)| gid.to_string())),
+            Self::Type => text.push(if entry.file_type.is_dir(){ 'd' } else if entry.file_type.is_symlink(){ 'l' } else{ 'f' }),
+            Self::Link => {
+                if entry.file_type.is_symlink(){
+                    text.push_str(&fs::read_link(&entry.path).map(|target| target.to_string_lossy().to_string()).unwrap_or_default());
+                }
+            }
+        }
+    }
+}
+
+impl Action{
+    pub fn execute(&self, candidate: &Candidate, output: &mut Output) -> bool{
+        let path = &candidate.entry.path;
+        match self{
+            Self::Prune => true,
+            Self::Print if output.json => {
+                output.text.push_str(&Self::json(candidate));
+                output.text.push('\n');
+                true
+            },
+            Self::Print => {
+                output.text.push_str(path);
+                output.text.push('\n');
+                true
+            },
+            Self::Print0 => {
+                output.text.push_str(path);
+                output.text.push('\0');
+                true
+            },
+            Self::Printf(directives) => {
+                directives.iter().for_each(|directive| directive.write(candidate, &mut output.text));
+                true
+            },
+            Self::Exec(command) => {
+                let command = command.iter().map(|argument| argument.replace("{}", path)).collect();
+                output.run(command).unwrap_or(false)
+            },
+            Self::ExecBatch(id, command) => {
+                output.add_to_batch(*id, command, path);
+                true
+            },
+            Self::Delete => {
+                output.deletions.push(candidate.entry.clone());
+                true
+            },
+            Self::Ls => {
+                output.text.push_str(&Self::ls(candidate, output.now));
+                output.text.push('\n');
+                true
+            }
+        }
+    }
+
+    // Describes an item as ls -dils does, but with the times in UTC: inode, 1 KiB blocks, permissions, hard links, user, group, size, modification time and path
+    fn ls(candidate: &Candidate, now: SystemTime) -> String{
+        let entry = candidate.entry;
+        let metadata = candidate.metadata();
+        let (inode, links, blocks) = metadata.map_or((0, 0, 0), info::inode);
+        let (user, group) = metadata
+            .and_then(info::owner)
+            .map_or((String::new(), String::new()), |(uid, gid)| (info::lookup_name(info::USERS, uid), info::lookup_name(info::GROUPS, gid)));
+        let modified = metadata.and_then(|metadata| metadata.modified().ok()).unwrap_or(UNIX_EPOCH);
+        let mut line = format!(
+            "{:>7} {:>6} {} {:>3} {:<8} {:<8} {:>8} {} {}",
+            inode,
+            blocks,
+            info::symbolic_permissions(entry.file_type, metadata.map_or(0, info::permissions)),
+            links,
+            user,
+            group,
+            metadata.map_or(0, |metadata| metadata.len()),
+            info::ls_time(modified, now),
+            entry.path
+        );
+        if entry.file_type.is_symlink(){
+            if let Ok(target) = fs::read_link(&entry.path){
+                line.push_str(&format!(" -> {}", target.to_string_lossy()));
+            }
+        }
+        line
+    }
+
+    // Describes an item as a JSON object with its path, depth, type, size and modification time. The size and the time are null if the metadata of the item cannot be read
+    fn json(candidate: &Candidate) -> String{
+        let entry = candidate.entry;
+        let metadata = candidate.metadata();
+        let file_type = if entry.file_type.is_dir(){ "dir" } else if entry.file_type.is_symlink(){ "symlink" } else{ "file" };
+        let size = metadata.map_or("null".to_string(), |metadata| metadata.len().to_string());
+        let mtime = metadata
+            .and_then(|metadata| metadata.modified().ok())
+            .map(|modified| modified.duration_since(UNIX_EPOCH).unwrap_or_default())
+            .map_or("null".to_string(), |since_epoch| format!("{{\"secs\":{},\"nanos\":{}}}", since_epoch.as_secs(), since_epoch.subsec_nanos()));
+        format!(
+            "{{\"type\":\"entry\",\"data\":{{\"path\":{},\"depth\":{},\"file_type\":\"{}\",\"size\":{},\"mtime\":{}}}}}",
+            base::json_text(entry.path.as_bytes()),
+            entry.depth,
+            file_type,
+            size,
+            mtime
+        )
+    }
+}
+
+impl Output{
+    pub fn new(now: SystemTime) -> Output{
+        Output{ now, json: false, text: String::new(), deletions: Vec::new(), errors: Vec::new(), batches: Vec::new() }
+    }
+
+    // Runs a command, adding what it writes in the standard output to the text. Its standard error goes straight to ours. Returns if it succeeded, or None if it couldn't even be executed, which is reported into errors
+    fn run(&mut self, command: Vec<String>) -> Option<bool>{
+        let (program, arguments) = command.split_first()?;
+        match Command::new(program).args(arguments).stderr(Stdio::inherit()).output(){
+            Ok(result) => {
+                self.text.push_str(&String::from_utf8_lossy(&result.stdout));
+                Some(result.status.success())
+            },
+            Err(_) => {
+                self.errors.push(CluErrors::InputError(format!("The command '{}' cannot be executed", program)));
+                None
+            }
+        }
+    }
+
+    fn add_to_batch(&mut self, id: usize, command: &[String], path: &str){
+        if self.batches.len() <= id{
+            self.batches.resize_with(id + 1, || None);
+        }
+        let batch = self.batches[id].get_or_insert_with(|| Batch{ command: command.to_vec(), paths: Vec::new(), length: 0 });
+        batch.paths.push(path.to_string());
+        batch.length += path.len() + 1;
+        if batch.length >= BATCH_LIMIT{
+            self.run_batch(id);
+        }
+    }
+
+    // Runs the command of a batch with its pending paths. A command failing makes find fail, as in GNU find
+    fn run_batch(&mut self, id: usize){
+        let Some(batch) = self.batches.get_mut(id).and_then(|batch| batch.take()) else { return };
+        let program = batch.command.first().cloned().unwrap_or_default();
+        let mut command = batch.command;
+        command.extend(batch.paths);
+        if self.run(command) == Some(false){
+            self.errors.push(CluErrors::InputError(format!("The command '{}' exited with an error", program)));
+        }
+    }
+
+    // Writes the text produced so far into out, leaving it empty
+    pub fn write_to(&mut self, out: &mut dyn Write) -> Result<(), CluErrors>{
+        out.write_all(self.text.as_bytes()).map_err(CluErrors::output)?;
+        self.text.clear();
+        Ok(())
+    }
+
+    // Runs the batches still pending
+    pub fn finish(&mut self){
+        (0..self.batches.len()).for_each(|id| self.run_batch(id));
+    }
+
+    // Deletes the items of -delete, which are given with the contents of every directory before the directory itself. Directories are only deleted if they're empty
+    pub fn delete(&mut self){
+        for entry in mem::take(&mut self.deletions){
+            let deleted = if entry.file_type.is_dir(){
+                fs::remove_dir(&entry.path)
+            }
+            else{
+                fs::remove_file(&entry.path)
+            };
+            if deleted.is_err(){
+                self.errors.push(CluErrors::InputError(format!("{} cannot be deleted", entry.path)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
@@ -0,0 +1,154 @@
+use std::{fs, path::PathBuf, time::{SystemTime, UNIX_EPOCH}};
+use crate::{walker::Entry, clu_errors::CluErrors, find::{expression::Candidate, actions::{Action, Directive, Output}}};
+
+// Builds an entry as the walker would do it, without following symbolic links
+fn entry(path: &str, depth: usize) -> Entry{
+    Entry{ path: path.to_string(), depth, file_type: fs::symlink_metadata(path).unwrap().file_type() }
+}
+
+// Builds a temporary directory for a test, with a file of 5 bytes inside
+fn temporary_dir(test: &str) -> PathBuf{
+    let mut root = std::env::temp_dir();
+    root.push(format!("amazing_clu_{}_{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("dir")).unwrap();
+    fs::write(root.join("dir").join("file.txt"), "hello").unwrap();
+    root
+}
+
+#[test]
+fn parse_directives_test(){
+    assert_eq!(vec![Directive::Path, Directive::Literal("\n".to_string())], Directive::parse("%p\\n").unwrap());
+    assert_eq!(
+        vec![Directive::Literal("size: ".to_string()), Directive::Size, Directive::Literal(" 100% \t".to_string()), Directive::MtimeSeconds],
+        Directive::parse("size: %s 100%% \\t%T@").unwrap()
+    );
+    assert_eq!(
+        vec![Directive::Name, Directive::Dir, Directive::Depth, Directive::Mode, Directive::Permissions, Directive::Mtime, Directive::User, Directive::Group, Directive::Uid, Directive::Gid, Directive::Type, Directive::Link],
+        Directive::parse("%f%h%d%m%M%t%u%g%U%G%y%l").unwrap()
+    );
+    assert!(Directive::parse("").unwrap().is_empty());
+
+    // Unknown directives and escapes
+    assert_eq!(CluErrors::InputError("Invalid directive in the -printf format '%z'".to_string()), Directive::parse("%z").unwrap_err());
+    assert_eq!(CluErrors::InputError("Invalid directive in the -printf format '%T'".to_string()), Directive::parse("%T").unwrap_err());
+    assert_eq!(CluErrors::InputError("Invalid directive in the -printf format 'a\\q'".to_string()), Directive::parse("a\\q").unwrap_err());
+    assert_eq!(CluErrors::InputError("Invalid directive in the -printf format '%'".to_string()), Directive::parse("%").unwrap_err());
+}
+
+#[test]
+fn print_test(){
+    let root = temporary_dir("print_test");
+    let file = root.join("dir").join("file.txt");
+    let file = entry(file.to_str().unwrap(), 2);
+    let candidate = Candidate::new(&file);
+    let mut output = Output::new(SystemTime::now());
+
+    assert!(Action::Print.execute(&candidate, &mut output));
+    assert!(Action::Print0.execute(&candidate, &mut output));
+    assert_eq!(format!("{}\n{}\0", file.path, file.path), output.text);
+
+    let mut output = Output::new(SystemTime::now());
+    assert!(Action::Printf(Directive::parse("%f %s %d %y %h\\n").unwrap()).execute(&candidate, &mut output));
+    assert_eq!(format!("file.txt 5 2 f {}\n", root.join("dir").to_str().unwrap()), output.text);
+
+    // With json, -print describes the item as a JSON object
+    let mut output = Output::new(SystemTime::now());
+    output.json = true;
+    assert!(Action::Print.execute(&candidate, &mut output));
+    let since_epoch = fs::metadata(&file.path).unwrap().modified().unwrap().duration_since(UNIX_EPOCH).unwrap();
+    assert_eq!(
+        format!(
+            "{{\"type\":\"entry\",\"data\":{{\"path\":{{\"text\":\"{}\"}},\"depth\":2,\"file_type\":\"file\",\"size\":5,\"mtime\":{{\"secs\":{},\"nanos\":{}}}}}}}\n",
+            file.path,
+            since_epoch.as_secs(),
+            since_epoch.subsec_nanos()
+        ),
+        output.text
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&file.path, fs::Permissions::from_mode(0o640)).unwrap();
+        let candidate = Candidate::new(&file);
+        let mut output = Output::new(SystemTime::now());
+        assert!(Action::Printf(Directive::parse("%m %M").unwrap()).execute(&candidate, &mut output));
+        assert_eq!("640 -rw-r-----", output.text);
+
+        // The long listing contains the permissions, the size and the path
+        let mut output = Output::new(SystemTime::now());
+        assert!(Action::Ls.execute(&candidate, &mut output));
+        assert!(output.text.contains(" -rw-r----- "));
+        assert!(output.text.contains(&format!(" 5 {}", info_time(&file))));
+        assert!(output.text.ends_with(&format!("{}\n", file.path)));
+    }
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+// The modification time of an entry as -ls writes it
+#[cfg(unix)]
+fn info_time(entry: &Entry) -> String{
+    crate::find::info::ls_time(fs::metadata(&entry.path).unwrap().modified().unwrap(), SystemTime::now())
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_test(){
+    let root = temporary_dir("exec_test");
+    let file = root.join("dir").join("file.txt");
+    let file = entry(file.to_str().unwrap(), 2);
+    let dir = entry(root.join("dir").to_str().unwrap(), 1);
+    let command = |command: &str| command.split_whitespace().map(|argument| argument.to_string()).collect::<Vec<String>>();
+
+    // The command is run for every item, with {} replaced by the path, and its output is kept
+    let mut output = Output::new(SystemTime::now());
+    assert!(Action::Exec(command("echo found:{}")).execute(&Candidate::new(&file), &mut output));
+    assert!(!Action::Exec(command("test -d {}")).execute(&Candidate::new(&file), &mut output));
+    assert!(Action::Exec(command("test -d {}")).execute(&Candidate::new(&dir), &mut output));
+    assert_eq!(format!("found:{}\n", file.path), output.text);
+    assert!(output.errors.is_empty());
+
+    // Commands that cannot be executed
+    assert!(!Action::Exec(command("unexistent_command_for_clu {}")).execute(&Candidate::new(&file), &mut output));
+    assert_eq!(vec![CluErrors::InputError("The command 'unexistent_command_for_clu' cannot be executed".to_string())], output.errors);
+
+    // The batches are run once they're finished, with every path at the end of the command
+    let mut output = Output::new(SystemTime::now());
+    assert!(Action::ExecBatch(0, command("echo batch")).execute(&Candidate::new(&dir), &mut output));
+    assert!(Action::ExecBatch(0, command("echo batch")).execute(&Candidate::new(&file), &mut output));
+    assert!(Action::ExecBatch(1, command("false")).execute(&Candidate::new(&file), &mut output));
+    assert!(output.text.is_empty());
+    output.finish();
+    assert_eq!(format!("batch {} {}\n", dir.path, file.path), output.text);
+    assert_eq!(vec![CluErrors::InputError("The command 'false' exited with an error".to_string())], output.errors);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn delete_test(){
+    let root = temporary_dir("delete_test");
+    fs::create_dir_all(root.join("full")).unwrap();
+    fs::write(root.join("full").join("kept.txt"), "").unwrap();
+    let file = entry(root.join("dir").join("file.txt").to_str().unwrap(), 2);
+    let dir = entry(root.join("dir").to_str().unwrap(), 1);
+    let full = entry(root.join("full").to_str().unwrap(), 1);
+
+    // Nothing is deleted until it's confirmed
+    let mut output = Output::new(SystemTime::now());
+    assert!(Action::Delete.execute(&Candidate::new(&file), &mut output));
+    assert!(Action::Delete.execute(&Candidate::new(&dir), &mut output));
+    assert!(Action::Delete.execute(&Candidate::new(&full), &mut output));
+    assert!(root.join("dir").join("file.txt").exists());
+
+    // Directories are only deleted if they're empty
+    output.delete();
+    assert!(!root.join("dir").exists());
+    assert!(root.join("full").join("kept.txt").exists());
+    assert_eq!(vec![CluErrors::InputError(format!("{} cannot be deleted", full.path))], output.errors);
+    assert!(output.deletions.is_empty());
+
+    fs::remove_dir_all(&root).unwrap();
+}
\ No newline at end of file
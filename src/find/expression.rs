@@ -0,0 +1,476 @@
+use std::{fs::{self, Metadata}, cell::OnceCell, ffi::OsString, path::Path, time::{Duration, SystemTime}};
+use regex::bytes::Regex;
+use crate::{case::{self, Case}, clu_errors::CluErrors, walker::{Entry, glob::Glob}, find::{info, actions::{Action, Directive, Output}}};
+
+// The tokens that may start a find expression, with the number of arguments each of them takes. The arguments of -exec go up to a ; or a {} followed by a +
+pub const TOKENS: [(&str, usize); 34] = [
+    ("(", 0), (")", 0), ("!", 0), ("-not", 0), ("-a", 0), ("-and", 0), ("-o", 0), ("-or", 0),
+    ("-name", 1), ("-iname", 1), ("-path", 1), ("-ipath", 1), ("-regex", 1), ("-iregex", 1), ("-type", 1), ("-size", 1),
+    ("-empty", 0), ("-newer", 1), ("-mtime", 1), ("-mmin", 1), ("-perm", 1), ("-user", 1), ("-group", 1),
+    ("-print", 0), ("-print0", 0), ("-printf", 1), ("-exec", 0), ("-delete", 0), ("-ls", 0), ("-prune", 0),
+    ("-maxdepth", 1), ("-mindepth", 1), ("-depth", 0), ("-xdev", 0)
+];
+
+// Checks if arg ends the arguments of -exec, given the argument before it
+fn ends_exec(previous: Option<&str>, arg: &str) -> bool{
+    arg == ";" || (arg == "+" && previous == Some("{}"))
+}
+
+// Find mixes its own flags (eg, -H) with the tokens of the expression (eg, -name *.txt), which clap cannot tell apart as the latter start with a single -. This function moves the tokens of the expression after a --, where clap takes them as they are. The arguments after a -- written by the user are left untouched.
+pub fn split_expression(args: impl IntoIterator<Item = OsString>) -> Vec<OsString>{
+    let mut args: Vec<OsString> = args.into_iter().collect();
+    if args.get(1).is_none_or(|utility| utility != "find"){
+        return args;
+    }
+    let end = args.iter().position(|arg| arg == "--").unwrap_or(args.len());
+    let mut rest = args.split_off(end);
+    let mut expression = Vec::new();
+    let mut kept = Vec::new();
+    let mut remaining = args.into_iter();
+    while let Some(arg) = remaining.next(){
+        match TOKENS.iter().find(|(token, _arguments)| arg == *token){
+            Some(("-exec", _arguments)) => {
+                expression.push(arg);
+                for arg in remaining.by_ref(){
+                    let end = ends_exec(expression.last().and_then(|previous| previous.to_str()), &arg.to_string_lossy());
+                    expression.push(arg);
+                    if end{
+                        break;
+                    }
+                }
+            },
+            Some((_token, arguments)) => {
+                expression.push(arg);
+                expression.extend(remaining.by_ref().take(*arguments));
+            },
+            None => kept.push(arg)
+        }
+    }
+    if expression.is_empty(){
+        kept.append(&mut rest);
+        return kept;
+    }
+    kept.push(OsString::from("--"));
+    kept.append(&mut expression);
+    kept.extend(rest.into_iter().skip(1)); // The -- written by the user is already there
+    kept
+}
+
+// A find expression, built from the tokens given by the user
+#[derive(Debug)]
+pub enum Expression{
+    True,
+    Not(Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Test(Test),
+    Action(Action)
+}
+
+#[derive(Debug)]
+pub enum Test{
+    Name(Glob),
+    Path(Glob),
+    Regex(Regex),
+    Type(Type),
+    Size(Comparison, u64), // The size is compared in units of the given number of bytes, rounding up
+    Empty,
+    Newer(SystemTime),
+    Mtime(Comparison),
+    Mmin(Comparison),
+    Perm(Perm, u32),
+    User(u32),
+    Group(u32)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Type{
+    File,
+    Directory,
+    Link
+}
+
+// The options written in the expression that don't depend on the items but change how the whole tree is walked. Like in GNU find, they may be written anywhere in the expression and they're always true
+#[derive(Debug, Default, PartialEq)]
+pub struct Globals{
+    pub max_depth: Option<usize>, // -maxdepth
+    pub min_depth: usize, // -mindepth
+    pub contents_first: bool, // -depth
+    pub xdev: bool // -xdev
+}
+
+// How a number given to a test is compared: +N means greater than N, -N less than N and N exactly N
+#[derive(Debug, PartialEq)]
+pub enum Comparison{
+    Greater(u64),
+    Less(u64),
+    Equal(u64)
+}
+
+// How the permission bits given to -perm are compared: MODE means exactly these bits, -MODE all of these bits and /MODE any of these bits
+#[derive(Debug, PartialEq)]
+pub enum Perm{
+    Exact,
+    All,
+    Any
+}
+
+// An entry being evaluated. Its metadata is only read if a test needs it, and it's read once no matter how many tests need it
+pub struct Candidate<'a>{
+    pub entry: &'a Entry,
+    metadata: OnceCell<Option<Metadata>>
+}
+
+impl Comparison{
+    fn parse(value: &str) -> Option<Comparison>{
+        if let Some(number) = value.strip_prefix('+'){
+            number.parse().ok().map(Comparison::Greater)
+        }
+        else if let Some(number) = value.strip_prefix('-'){
+            number.parse().ok().map(Comparison::Less)
+        }
+        else{
+            value.parse().ok().map(Comparison::Equal)
+        }
+    }
+
+    fn matches(&self, value: u64) -> bool{
+        match self{
+            Self::Greater(number) => value > *number,
+            Self::Less(number) => value < *number,
+            Self::Equal(number) => value == *number
+        }
+    }
+}
+
+impl<'a> Candidate<'a>{
+    pub fn new(entry: &'a Entry) -> Candidate<'a>{
+        Candidate{ entry, metadata: OnceCell::new() }
+    }
+
+    // The metadata of the item a symbolic link points to if the walker followed it, otherwise the metadata of the entry itself
+    pub fn metadata(&self) -> Option<&Metadata>{
+        self.metadata.get_or_init(||{
+            if self.entry.file_type.is_symlink(){
+                fs::symlink_metadata(&self.entry.path).ok()
+            }
+            else{
+                fs::metadata(&self.entry.path).ok()
+            }
+        }).as_ref()
+    }
+
+    // The last component of the path, or the whole path if it doesn't have one (eg, / or ..)
+    pub fn name(&self) -> &str{
+        Path::new(&self.entry.path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.entry.path)
+    }
+}
+
+// Parses the tokens of an expression. The operators are, from the highest to the lowest precedence: ( ), ! or -not, -a or -and (which may be omitted) and -o or -or. Every -exec ... + found gets its own batch
+struct Parser<'a>{
+    tokens: &'a [String],
+    position: usize,
+    batches: usize,
+    globals: Globals,
+    case: Case
+}
+
+impl Expression{
+    // Parses the tokens of an expression, returning it with the global options found in it. Like in GNU find, if it doesn't have any action other than -prune, the items satisfying it are printed. The regexes of -regex and -iregex follow the case options of find
+    pub fn parse(tokens: &[String], case: Case) -> Result<(Expression, Globals), CluErrors>{
+        if tokens.is_empty(){
+            return Ok((Expression::Action(Action::Print), Globals::default()));
+        }
+        let mut parser = Parser{ tokens, position: 0, batches: 0, globals: Globals::default(), case };
+        let expression = parser.or()?;
+        if let Some(token) = parser.peek(){
+            return Err(CluErrors::InputError(format!("Unexpected '{}' in the find expression", token)));
+        }
+        if expression.any_action(&|action| !matches!(action, Action::Prune)){
+            Ok((expression, parser.globals))
+        }
+        else{
+            Ok((Expression::And(Box::new(expression), Box::new(Expression::Action(Action::Print))), parser.globals))
+        }
+    }
+
+    // Checks if the contents of a directory must be skipped because the evaluation of the expression reaches -prune. The actions aren't executed, they're considered successful instead
+    pub fn prunes(&self, candidate: &Candidate, now: SystemTime) -> bool{
+        let mut pruned = false;
+        self.dry_run(candidate, now, &mut pruned);
+        pruned
+    }
+
+    fn dry_run(&self, candidate: &Candidate, now: SystemTime, pruned: &mut bool) -> bool{
+        match self{
+            Self::True => true,
+            Self::Not(expression) => !expression.dry_run(candidate, now, pruned),
+            Self::And(left, right) => left.dry_run(candidate, now, pruned) && right.dry_run(candidate, now, pruned),
+            Self::Or(left, right) => left.dry_run(candidate, now, pruned) || right.dry_run(candidate, now, pruned),
+            Self::Test(test) => test.evaluate(candidate, now),
+            Self::Action(action) => {
+                *pruned |= matches!(action, Action::Prune);
+                true
+            }
+        }
+    }
+
+    // Checks if any action of the expression satisfies condition
+    pub fn any_action(&self, condition: &impl Fn(&Action) -> bool) -> bool{
+        match self{
+            Self::True => false,
+            Self::Not(expression) => expression.any_action(condition),
+            Self::And(left, right) | Self::Or(left, right) => left.any_action(condition) || right.any_action(condition),
+            Self::Test(_test) => false,
+            Self::Action(action) => condition(action)
+        }
+    }
+
+    pub fn evaluate(&self, candidate: &Candidate, output: &mut Output) -> bool{
+        match self{
+            Self::True => true,
+            Self::Not(expression) => !expression.evaluate(candidate, output),
+            Self::And(left, right) => left.evaluate(candidate, output) && right.evaluate(candidate, output),
+            Self::Or(left, right) => left.evaluate(candidate, output) || right.evaluate(candidate, output),
+            Self::Test(test) => test.evaluate(candidate, output.now),
+            Self::Action(action) => action.execute(candidate, output)
+        }
+    }
+}
+
+impl<'a> Parser<'a>{
+    fn peek(&self) -> Option<&str>{
+        self.tokens.get(self.position).map(|token| token.as_str())
+    }
+
+    fn next(&mut self) -> Option<&'a str>{
+        let token = self.tokens.get(self.position).map(|token| token.as_str());
+        self.position += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expression, CluErrors>{
+        let mut expression = self.and()?;
+        while matches!(self.peek(), Some("-o") | Some("-or")){
+            self.position += 1;
+            expression = Expression::Or(Box::new(expression), Box::new(self.and()?));
+        }
+        Ok(expression)
+    }
+
+    fn and(&mut self) -> Result<Expression, CluErrors>{
+        let mut expression = self.not()?;
+        loop{
+            match self.peek(){
+                Some("-a") | Some("-and") => self.position += 1,
+                Some("-o") | Some("-or") | Some(")") | None => return Ok(expression),
+                Some(_) => () // Two expressions one after the other are joined by an implicit -and
+            }
+            expression = Expression::And(Box::new(expression), Box::new(self.not()?));
+        }
+    }
+
+    fn not(&mut self) -> Result<Expression, CluErrors>{
+        if matches!(self.peek(), Some("!") | Some("-not")){
+            self.position += 1;
+            return Ok(Expression::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expression, CluErrors>{
+        let token = match self.next(){
+            Some(token) => token.to_string(),
+            None => return Err(CluErrors::InputError("The find expression ended unexpectedly".to_string()))
+        };
+        match token.as_str(){
+            "(" => {
+                let expression = self.or()?;
+                match self.next(){
+                    Some(")") => Ok(expression),
+                    _ => Err(CluErrors::InputError("Missing ')' in the find expression".to_string()))
+                }
+            },
+            "-empty" => Ok(Expression::Test(Test::Empty)),
+            "-print" => Ok(Expression::Action(Action::Print)),
+            "-print0" => Ok(Expression::Action(Action::Print0)),
+            "-delete" => Ok(Expression::Action(Action::Delete)),
+            "-ls" => Ok(Expression::Action(Action::Ls)),
+            "-prune" => Ok(Expression::Action(Action::Prune)),
+            "-depth" => {
+                self.globals.contents_first = true;
+                Ok(Expression::True)
+            },
+            "-xdev" => {
+                self.globals.xdev = true;
+                Ok(Expression::True)
+            },
+            "-maxdepth" | "-mindepth" => {
+                let depth = match self.next(){
+                    Some(value) => value.parse().map_err(|_err| CluErrors::InputError(format!("Invalid argument '{}' to {}", value, token)))?,
+                    None => return Err(CluErrors::InputError(format!("Missing argument to {}", token)))
+                };
+                if token == "-maxdepth"{
+                    self.globals.max_depth = Some(depth);
+                }
+                else{
+                    self.globals.min_depth = depth;
+                }
+                Ok(Expression::True)
+            },
+            "-exec" => self.exec(),
+            "-printf" => match self.next(){
+                Some(format) => Directive::parse(format).map(|directives| Expression::Action(Action::Printf(directives))),
+                None => Err(CluErrors::InputError(format!("Missing argument to {}", token)))
+            },
+            _ if TOKENS.contains(&(token.as_str(), 1)) => match self.next(){
+                Some(value) => Test::parse(&token, value, self.case).map(Expression::Test),
+                None => Err(CluErrors::InputError(format!("Missing argument to {}", token)))
+            },
+            _ => Err(CluErrors::InputError(format!("Unexpected '{}' in the find expression", token)))
+        }
+    }
+
+    // Parses the command of -exec, which ends with ; to run it for every item or with {} + to run it for batches of items
+    fn exec(&mut self) -> Result<Expression, CluErrors>{
+        let mut command: Vec<String> = Vec::new();
+        loop{
+            let previous = command.last().map(|argument| argument.as_str());
+            match self.next(){
+                Some(arg) if ends_exec(previous, arg) => {
+                    let batch = arg == "+";
+                    if batch{
+                        command.pop(); // The paths are added in place of the {}
+                    }
+                    if command.is_empty(){
+                        return Err(CluErrors::InputError("Missing command to -exec".to_string()));
+                    }
+                    if !batch{
+                        return Ok(Expression::Action(Action::Exec(command)));
+                    }
+                    self.batches += 1;
+                    return Ok(Expression::Action(Action::ExecBatch(self.batches - 1, command)));
+                },
+                Some(arg) => command.push(arg.to_string()),
+                None => return Err(CluErrors::InputError("Missing ';' or '{} +' at the end of -exec".to_string()))
+            }
+        }
+    }
+}
+
+impl Test{
+    fn parse(token: &str, value: &str, case: Case) -> Result<Test, CluErrors>{
+        let invalid = || CluErrors::InputError(format!("Invalid argument '{}' to {}", value, token));
+        let test = match token{
+            "-name" => Test::Name(Glob::new(value, false)),
+            "-iname" => Test::Name(Glob::new(value, true)),
+            "-path" => Test::Path(Glob::new(value, false)),
+            "-ipath" => Test::Path(Glob::new(value, true)),
+            "-regex" | "-iregex" => {
+                // Like in GNU find, the regex must match the whole path. -iregex is always case insensitive, while -regex follows -i and -S, the latter looking at the regex itself
+                let insensitive = token == "-iregex" || case.is_insensitive(&[value.to_string()]);
+                let regex = case::builder(&format!("^(?:{})$", value), insensitive, case.case_folding)
+                    .build()
+                    .map_err(|_err| CluErrors::RegexError(value.to_string()))?;
+                Test::Regex(regex)
+            },
+            "-type" => match value{
+                "f" => Test::Type(Type::File),
+                "d" => Test::Type(Type::Directory),
+                "l" => Test::Type(Type::Link),
+                _ => return Err(invalid())
+            },
+            "-size" => {
+                // The size may end with a unit: c for bytes, w for 2 bytes, b for 512 bytes blocks (the default), k for KiB, M for MiB and G for GiB
+                let (number, unit) = match value.char_indices().last(){
+                    Some((position, unit)) if unit.is_ascii_alphabetic() => (&value[..position], unit),
+                    _ => (value, 'b')
+                };
+                let unit = match unit{
+                    'c' => 1,
+                    'w' => 2,
+                    'b' => 512,
+                    'k' => 1024,
+                    'M' => 1024 * 1024,
+                    'G' => 1024 * 1024 * 1024,
+                    _ => return Err(invalid())
+                };
+                Test::Size(Comparison::parse(number).ok_or_else(invalid)?, unit)
+            },
+            "-newer" => {
+                let modified = fs::metadata(value)
+                    .and_then(|metadata| metadata.modified())
+                    .map_err(|_err| CluErrors::InputError(format!("The modification time of '{}' cannot be read", value)))?;
+                Test::Newer(modified)
+            },
+            "-mtime" => Test::Mtime(Comparison::parse(value).ok_or_else(invalid)?),
+            "-mmin" => Test::Mmin(Comparison::parse(value).ok_or_else(invalid)?),
+            "-perm" => {
+                // Only octal modes are supported
+                let (perm, mode) = if let Some(mode) = value.strip_prefix('-'){
+                    (Perm::All, mode)
+                }
+                else if let Some(mode) = value.strip_prefix('/'){
+                    (Perm::Any, mode)
+                }
+                else{
+                    (Perm::Exact, value)
+                };
+                Test::Perm(perm, u32::from_str_radix(mode, 8).map_err(|_err| invalid())?)
+            },
+            "-user" => Test::User(
+                value.parse().ok()
+                    .or_else(|| info::lookup_id(info::USERS, value))
+                    .ok_or_else(|| CluErrors::InputError(format!("'{}' is not the name of a known user", value)))?
+            ),
+            "-group" => Test::Group(
+                value.parse().ok()
+                    .or_else(|| info::lookup_id(info::GROUPS, value))
+                    .ok_or_else(|| CluErrors::InputError(format!("'{}' is not the name of a known group", value)))?
+            ),
+            _ => return Err(CluErrors::InputError(format!("Unexpected '{}' in the find expression", token)))
+        };
+        Ok(test)
+    }
+
+    fn evaluate(&self, candidate: &Candidate, now: SystemTime) -> bool{
+        let file_type = candidate.entry.file_type;
+        // How long ago the item was modified, in the given unit of time. A modification time in the future is considered now
+        let age = |unit: u64| candidate.metadata()
+            .and_then(|metadata| metadata.modified().ok())
+            .map(|modified| now.duration_since(modified).unwrap_or(Duration::ZERO).as_secs() / unit);
+        match self{
+            Self::Name(glob) => glob.is_match(candidate.name()),
+            Self::Path(glob) => glob.is_match(&candidate.entry.path),
+            Self::Regex(regex) => regex.is_match(candidate.entry.path.as_bytes()),
+            Self::Type(Type::File) => file_type.is_file(),
+            Self::Type(Type::Directory) => file_type.is_dir(),
+            Self::Type(Type::Link) => file_type.is_symlink(),
+            Self::Size(comparison, unit) => candidate.metadata().is_some_and(|metadata| comparison.matches(metadata.len().div_ceil(*unit))),
+            Self::Empty => {
+                if file_type.is_dir(){
+                    fs::read_dir(&candidate.entry.path).is_ok_and(|mut contents| contents.next().is_none())
+                }
+                else{
+                    file_type.is_file() && candidate.metadata().is_some_and(|metadata| metadata.len() == 0)
+                }
+            },
+            Self::Newer(time) => candidate.metadata().and_then(|metadata| metadata.modified().ok()).is_some_and(|modified| modified > *time),
+            Self::Mtime(comparison) => age(24 * 60 * 60).is_some_and(|days| comparison.matches(days)),
+            Self::Mmin(comparison) => age(60).is_some_and(|minutes| comparison.matches(minutes)),
+            Self::Perm(perm, mode) => candidate.metadata().map(info::permissions).is_some_and(|permissions| match perm{
+                Perm::Exact => permissions == *mode,
+                Perm::All => permissions & mode == *mode,
+                Perm::Any => *mode == 0 || permissions & mode != 0
+            }),
+            Self::User(uid) => candidate.metadata().and_then(info::owner).is_some_and(|(owner, _group)| owner == *uid),
+            Self::Group(gid) => candidate.metadata().and_then(info::owner).is_some_and(|(_owner, group)| group == *gid)
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
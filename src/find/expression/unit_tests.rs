@@ -0,0 +1,245 @@
+use std::{fs, ffi::OsString, path::PathBuf, time::{Duration, SystemTime}};
+use crate::{case::{Case, Folding}, walker::Entry, find::{expression::{self, Expression, Candidate, Globals}, actions::Output}, clu_errors::CluErrors};
+
+// Builds an entry as the walker would do it, without following symbolic links
+fn entry(path: &str) -> Entry{
+    Entry{ path: path.to_string(), depth: 0, file_type: fs::symlink_metadata(path).unwrap().file_type() }
+}
+
+// The case options of find by default, which keep the regexes case sensitive
+const SENSITIVE: Case = Case{ ignore_case: false, smart_case: false, no_ignore_case: false, case_folding: Folding::Unicode };
+
+// Evaluates an expression written as the user would do it against path
+fn evaluate(expression: &str, path: &str, now: SystemTime) -> bool{
+    evaluate_with_case(expression, path, now, SENSITIVE)
+}
+
+// Evaluates an expression against path as find would do it with the given case options
+fn evaluate_with_case(expression: &str, path: &str, now: SystemTime, case: Case) -> bool{
+    let tokens: Vec<String> = expression.split_whitespace().map(|token| token.to_string()).collect();
+    Expression::parse(&tokens, case).unwrap().0.evaluate(&Candidate::new(&entry(path)), &mut Output::new(now))
+}
+
+// Parses an expression written as the user would do it
+fn parse(expression: &str) -> Result<Expression, CluErrors>{
+    parse_globals(expression).map(|(expression, _globals)| expression)
+}
+
+// Parses an expression written as the user would do it, keeping its global options
+fn parse_globals(expression: &str) -> Result<(Expression, Globals), CluErrors>{
+    Expression::parse(&expression.split_whitespace().map(|token| token.to_string()).collect::<Vec<String>>(), SENSITIVE)
+}
+
+#[test]
+fn split_expression_test(){
+    let args = |args: &str| args.split_whitespace().map(OsString::from).collect::<Vec<OsString>>();
+
+    // The tokens of the expression are moved after a --, the flags of find are kept before it
+    assert_eq!(args("clu find . -H -- -name *.txt -o ( -type d )"), expression::split_expression(args("clu find . -name *.txt -H -o ( -type d )")));
+    // The arguments of a test are moved with it, even if they look like a flag
+    assert_eq!(args("clu find . -- -mtime -3 -size -1k"), expression::split_expression(args("clu find . -mtime -3 -size -1k")));
+    // The arguments of -exec go up to a ; or a {} followed by a +
+    assert_eq!(args("clu find . -H -- -exec ls -H {} ; -print"), expression::split_expression(args("clu find . -exec ls -H {} ; -H -print")));
+    assert_eq!(args("clu find . -H -- -exec ls + -j {} + -print"), expression::split_expression(args("clu find . -exec ls + -j {} + -H -print")));
+    // Everything after a -- written by the user is left untouched
+    assert_eq!(args("clu find . -- -name x -name y"), expression::split_expression(args("clu find . -name x -- -name y")));
+    assert_eq!(args("clu find . -- -name y"), expression::split_expression(args("clu find . -- -name y")));
+    // Nothing changes without an expression or in other utilities
+    assert_eq!(args("clu find . -H -j 2"), expression::split_expression(args("clu find . -H -j 2")));
+    assert_eq!(args("clu grep -o pattern ."), expression::split_expression(args("clu grep -o pattern .")));
+}
+
+#[test]
+fn parse_test(){
+    // An empty expression matches and prints everything
+    let mut output = Output::new(SystemTime::now());
+    assert!(Expression::parse(&[], SENSITIVE).unwrap().0.evaluate(&Candidate::new(&entry("./tests")), &mut output));
+    assert_eq!("./tests\n", output.text);
+
+    // The items are printed if the expression doesn't have any action, but only if they satisfy the expression
+    let mut output = Output::new(SystemTime::now());
+    assert!(parse("-name *.txt -o -type d").unwrap().evaluate(&Candidate::new(&entry("./tests")), &mut output));
+    assert!(!parse("-name *.txt").unwrap().evaluate(&Candidate::new(&entry("./tests")), &mut output));
+    assert_eq!("./tests\n", output.text);
+    let mut output = Output::new(SystemTime::now());
+    assert!(!parse("-name *.txt -print0").unwrap().evaluate(&Candidate::new(&entry("./tests")), &mut output));
+    assert!(parse("-print0 -name *.txt -o -print").unwrap().evaluate(&Candidate::new(&entry("./tests")), &mut output));
+    assert_eq!("./tests\0./tests\n", output.text);
+
+    // Syntax errors
+    assert_eq!(CluErrors::InputError("Missing argument to -name".to_string()), parse("-name").unwrap_err());
+    assert_eq!(CluErrors::InputError("Missing ')' in the find expression".to_string()), parse("( -name x").unwrap_err());
+    assert_eq!(CluErrors::InputError("Unexpected ')' in the find expression".to_string()), parse("-name x )").unwrap_err());
+    assert_eq!(CluErrors::InputError("The find expression ended unexpectedly".to_string()), parse("-name x -o").unwrap_err());
+    assert_eq!(CluErrors::InputError("Unexpected 'x' in the find expression".to_string()), parse("x").unwrap_err());
+    assert_eq!(CluErrors::InputError("Missing ';' or '{} +' at the end of -exec".to_string()), parse("-exec echo {}").unwrap_err());
+    assert_eq!(CluErrors::InputError("Missing command to -exec".to_string()), parse("-exec ;").unwrap_err());
+    assert_eq!(CluErrors::InputError("Missing command to -exec".to_string()), parse("-exec {} +").unwrap_err());
+    assert_eq!(CluErrors::InputError("Missing argument to -printf".to_string()), parse("-printf").unwrap_err());
+
+    // Invalid arguments
+    assert_eq!(CluErrors::InputError("Invalid argument 'x' to -type".to_string()), parse("-type x").unwrap_err());
+    assert_eq!(CluErrors::InputError("Invalid argument '1x' to -size".to_string()), parse("-size 1x").unwrap_err());
+    assert_eq!(CluErrors::InputError("Invalid argument 'a' to -mtime".to_string()), parse("-mtime a").unwrap_err());
+    assert_eq!(CluErrors::InputError("Invalid argument 'u+x' to -perm".to_string()), parse("-perm u+x").unwrap_err());
+    assert_eq!(CluErrors::RegexError("[a-z".to_string()), parse("-regex [a-z").unwrap_err());
+    assert_eq!(CluErrors::InputError("The modification time of 'unexistent.txt' cannot be read".to_string()), parse("-newer unexistent.txt").unwrap_err());
+    assert_eq!(CluErrors::InputError("'unexistent_user' is not the name of a known user".to_string()), parse("-user unexistent_user").unwrap_err());
+}
+
+#[test]
+fn globals_test(){
+    // The global options are collected wherever they are, and they're always true
+    let (expression, globals) = parse_globals("-maxdepth 2 -name *.txt -mindepth 1 -o -depth -xdev").unwrap();
+    assert_eq!(Globals{ max_depth: Some(2), min_depth: 1, contents_first: true, xdev: true }, globals);
+    assert!(expression.evaluate(&Candidate::new(&entry("./tests")), &mut Output::new(SystemTime::now())));
+    assert_eq!(Globals::default(), parse_globals("-name *.txt").unwrap().1);
+
+    // Invalid depths
+    assert_eq!(CluErrors::InputError("Missing argument to -maxdepth".to_string()), parse("-maxdepth").unwrap_err());
+    assert_eq!(CluErrors::InputError("Invalid argument '-1' to -maxdepth".to_string()), parse("-maxdepth -1").unwrap_err());
+    assert_eq!(CluErrors::InputError("Invalid argument 'a' to -mindepth".to_string()), parse("-mindepth a").unwrap_err());
+}
+
+#[test]
+fn prune_test(){
+    let now = SystemTime::now();
+    let (tests, src) = (entry("./tests"), entry("./src")); // Two directories
+    let (tests, src) = (Candidate::new(&tests), Candidate::new(&src));
+
+    // The directories reaching -prune are pruned
+    assert!(parse("-name tests -prune").unwrap().prunes(&tests, now));
+    assert!(!parse("-name tests -prune").unwrap().prunes(&src, now));
+    assert!(parse("-name tests -prune -o -print").unwrap().prunes(&tests, now));
+    assert!(!parse("-name tests -o -prune").unwrap().prunes(&tests, now));
+    assert!(!parse("-name tests -exec false ; -o -prune").unwrap().prunes(&tests, now)); // The actions are assumed to succeed
+
+    // -prune still prints the items if there isn't any other action, but nothing is printed while checking it
+    let mut output = Output::new(now);
+    assert!(parse("-name tests -prune").unwrap().evaluate(&tests, &mut output));
+    assert_eq!("./tests\n", output.text);
+    let mut output = Output::new(now);
+    assert!(parse("-name tests -prune -o -print").unwrap().evaluate(&tests, &mut output));
+    assert!(parse("-name tests -prune -o -print").unwrap().prunes(&tests, now));
+    assert_eq!("", output.text);
+}
+
+#[test]
+fn operators_test(){
+    let now = SystemTime::now();
+    let file = "./tests/grep_files/sample_text.txt";
+
+    // -and, written or implicit, and -or
+    assert!(evaluate("-name *.txt -type f", file, now));
+    assert!(evaluate("-name *.txt -a -type f", file, now));
+    assert!(!evaluate("-name *.txt -and -type d", file, now));
+    assert!(evaluate("-name *.rs -o -type f", file, now));
+    assert!(!evaluate("-name *.rs -or -type d", file, now));
+
+    // Negation
+    assert!(evaluate("! -name *.rs", file, now));
+    assert!(!evaluate("-not -name *.txt", file, now));
+    assert!(evaluate("-not -not -name *.txt", file, now));
+
+    // -and takes precedence over -or, unless there are parentheses
+    assert!(evaluate("-name *.txt -o -name *.rs -type d", file, now));
+    assert!(!evaluate("( -name *.txt -o -name *.rs ) -type d", file, now));
+    assert!(evaluate("! ( -name *.rs -o -type d )", file, now));
+}
+
+#[test]
+fn tests_test(){
+    let now = SystemTime::now();
+    let file = "./tests/grep_files/sample_text.txt"; // 59 bytes
+    let dir = "./tests/grep_files";
+
+    // Names and paths
+    assert!(evaluate("-name sample_text.txt", file, now));
+    assert!(evaluate("-name sample_*.t?t", file, now));
+    assert!(!evaluate("-name SAMPLE_TEXT.TXT", file, now));
+    assert!(evaluate("-iname SAMPLE_TEXT.TXT", file, now));
+    assert!(evaluate("-name grep_files", dir, now));
+    assert!(evaluate("-path */grep_files/*.txt", file, now));
+    assert!(!evaluate("-path *.rs", file, now));
+    assert!(evaluate("-ipath */GREP_FILES/*", file, now));
+
+    // The regex must match the whole path
+    assert!(evaluate("-regex .*sample_text\\.txt", file, now));
+    assert!(!evaluate("-regex sample_text", file, now));
+    assert!(evaluate("-iregex .*SAMPLE.*", file, now));
+    // -regex follows -i, and -S unless the regex has an uppercase letter
+    let insensitive = Case{ ignore_case: true, ..SENSITIVE };
+    let smart = Case{ smart_case: true, ..SENSITIVE };
+    assert!(evaluate_with_case("-regex .*SAMPLE.*", file, now, insensitive));
+    assert!(!evaluate_with_case("-regex .*SAMPLE.*", file, now, smart));
+    assert!(evaluate_with_case("-regex .*\\S+_text.*", file, now, smart));
+
+    // Types
+    assert!(evaluate("-type f", file, now));
+    assert!(!evaluate("-type d", file, now));
+    assert!(evaluate("-type d", dir, now));
+    assert!(!evaluate("-type l", dir, now));
+
+    // Sizes, rounded up to the unit
+    assert!(evaluate("-size 59c", file, now));
+    assert!(evaluate("-size +58c", file, now));
+    assert!(evaluate("-size -60c", file, now));
+    assert!(evaluate("-size 1", file, now));
+    assert!(evaluate("-size 1k", file, now));
+    assert!(!evaluate("-size -1k", file, now));
+    assert!(!evaluate("-size +1M", file, now));
+    assert!(!evaluate("-empty", file, now));
+    assert!(!evaluate("-empty", dir, now));
+}
+
+#[test]
+fn metadata_tests_test(){
+    // Build a directory with an empty file and an empty directory, modified now
+    let mut root = std::env::temp_dir();
+    root.push(format!("amazing_clu_metadata_tests_test_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("empty_dir")).unwrap();
+    fs::write(root.join("empty_file"), "").unwrap();
+    let path = |name: &str| -> String{
+        let path: PathBuf = root.join(name);
+        path.to_str().unwrap().to_string()
+    };
+    let now = SystemTime::now();
+
+    // Empty items
+    assert!(evaluate("-empty", &path("empty_dir"), now));
+    assert!(evaluate("-empty", &path("empty_file"), now));
+    assert!(!evaluate("-empty", &path(""), now));
+
+    // Modification times, comparing from several moments
+    let in_three_days = now + Duration::from_secs(3 * 24 * 60 * 60 + 60);
+    assert!(evaluate("-mtime 0", &path("empty_file"), now));
+    assert!(evaluate("-mtime -1", &path("empty_file"), now));
+    assert!(evaluate("-mtime 3", &path("empty_file"), in_three_days));
+    assert!(evaluate("-mtime +2", &path("empty_file"), in_three_days));
+    assert!(!evaluate("-mtime -3", &path("empty_file"), in_three_days));
+    assert!(evaluate("-mmin -5", &path("empty_file"), now));
+    assert!(evaluate("-mmin +4000", &path("empty_file"), in_three_days));
+    assert!(!evaluate(&format!("-newer {}", path("empty_file")), &path("empty_file"), now));
+
+    // Permissions and owners
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{PermissionsExt, MetadataExt};
+        fs::set_permissions(root.join("empty_file"), fs::Permissions::from_mode(0o640)).unwrap();
+        assert!(evaluate("-perm 640", &path("empty_file"), now));
+        assert!(!evaluate("-perm 600", &path("empty_file"), now));
+        assert!(evaluate("-perm -600", &path("empty_file"), now));
+        assert!(!evaluate("-perm -644", &path("empty_file"), now));
+        assert!(!evaluate("-perm /004", &path("empty_file"), now));
+        assert!(evaluate("-perm /044", &path("empty_file"), now));
+
+        let metadata = fs::metadata(root.join("empty_file")).unwrap();
+        assert!(evaluate(&format!("-user {}", metadata.uid()), &path("empty_file"), now));
+        assert!(!evaluate(&format!("-user {}", metadata.uid() + 1), &path("empty_file"), now));
+        assert!(evaluate(&format!("-group {}", metadata.gid()), &path("empty_file"), now));
+        assert_eq!(metadata.uid() == 0, evaluate("-user root", &path("empty_file"), now));
+    }
+
+    fs::remove_dir_all(&root).unwrap();
+}
\ No newline at end of file
@@ -0,0 +1,125 @@
+use std::{fs::{self, Metadata, FileType}, time::{SystemTime, UNIX_EPOCH}};
+
+// The user and group databases, whose lines look like name:password:id:...
+pub const USERS: &str = "/etc/passwd";
+pub const GROUPS: &str = "/etc/group";
+
+const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"]; // 1970-01-01 was a Thursday
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+// Finds the id of a user or group by its name in a database
+pub fn lookup_id(database: &str, name: &str) -> Option<u32>{
+    fs::read_to_string(database).ok()?
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<&str>>())
+        .find(|fields| fields.first() == Some(&name))
+        .and_then(|fields| fields.get(2)?.parse().ok())
+}
+
+// Finds the name of a user or group by its id in a database, falling back to the id itself if it isn't there
+pub fn lookup_name(database: &str, id: u32) -> String{
+    fs::read_to_string(database).ok()
+        .and_then(|contents| contents
+            .lines()
+            .map(|line| line.split(':').collect::<Vec<&str>>())
+            .find(|fields| fields.get(2) == Some(&id.to_string().as_str()))
+            .and_then(|fields| fields.first().map(|name| name.to_string()))
+        )
+        .unwrap_or(id.to_string())
+}
+
+// The permission bits of an item. Out of Unix, only the read only attribute is available, so it's translated to the equivalent bits
+#[cfg(unix)]
+pub fn permissions(metadata: &Metadata) -> u32{
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+pub fn permissions(metadata: &Metadata) -> u32{
+    if metadata.permissions().readonly(){ 0o444 } else{ 0o666 }
+}
+
+// The user and group owning an item, which are only available in Unix
+#[cfg(unix)]
+pub fn owner(metadata: &Metadata) -> Option<(u32, u32)>{
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.uid(), metadata.gid()))
+}
+
+#[cfg(not(unix))]
+pub fn owner(_metadata: &Metadata) -> Option<(u32, u32)>{
+    None
+}
+
+// The inode, number of hard links and 1 KiB blocks used by an item, which are only available in Unix
+#[cfg(unix)]
+pub fn inode(metadata: &Metadata) -> (u64, u64, u64){
+    use std::os::unix::fs::MetadataExt;
+    (metadata.ino(), metadata.nlink(), metadata.blocks() / 2) // Unix counts blocks of 512 bytes
+}
+
+#[cfg(not(unix))]
+pub fn inode(metadata: &Metadata) -> (u64, u64, u64){
+    (0, 1, metadata.len().div_ceil(1024))
+}
+
+// The permissions written as ls does, eg: drwxr-xr-x
+pub fn symbolic_permissions(file_type: FileType, mode: u32) -> String{
+    let kind = if file_type.is_dir(){ 'd' } else if file_type.is_symlink(){ 'l' } else{ '-' };
+    let mut symbolic = String::from(kind);
+    // Every class has its read, write and execute bits, and the execute one is replaced if the special bit of the class is set: setuid, setgid or sticky
+    for (shift, special, letter) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')]{
+        let bits = (mode >> shift) & 0o7;
+        symbolic.push(if bits & 0o4 != 0{ 'r' } else{ '-' });
+        symbolic.push(if bits & 0o2 != 0{ 'w' } else{ '-' });
+        symbolic.push(match (bits & 0o1 != 0, mode & special != 0){
+            (true, true) => letter,
+            (false, true) => letter.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-'
+        });
+    }
+    symbolic
+}
+
+// Splits a time into its date and time of the day in UTC: (year, month from 1, day from 1, hour, minute, second, day of the week from 0 on Thursday)
+fn civil(time: SystemTime) -> (i64, usize, i64, i64, i64, i64, usize){
+    let seconds = match time.duration_since(UNIX_EPOCH){
+        Ok(duration) => duration.as_secs() as i64,
+        Err(err) => -(err.duration().as_secs() as i64)
+    };
+    let (days, day_seconds) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+    // Converts the days since the epoch into a date of the Gregorian calendar, counting from March so the leap day is the last of the year
+    let shifted = days + 719468;
+    let era = shifted.div_euclid(146097);
+    let day_of_era = shifted.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10{ month_from_march + 3 } else{ month_from_march - 9 };
+    let year = year_of_era + era * 400 + if month <= 2{ 1 } else{ 0 };
+    (year, month as usize, day, day_seconds / 3600, day_seconds % 3600 / 60, day_seconds % 60, days.rem_euclid(7) as usize)
+}
+
+// A time written as the C function ctime does, in UTC, eg: Sat Oct 18 06:57:00 2026
+pub fn ctime(time: SystemTime) -> String{
+    let (year, month, day, hour, minute, second, weekday) = civil(time);
+    format!("{} {} {:2} {:02}:{:02}:{:02} {}", DAYS[weekday], MONTHS[month - 1], day, hour, minute, second, year)
+}
+
+// A time written as ls does, in UTC: the hour for recent times and the year for those older than six months or in the future, eg: Oct 18 06:57 or Oct 18  2025
+pub fn ls_time(time: SystemTime, now: SystemTime) -> String{
+    let (year, month, day, hour, minute, _second, _weekday) = civil(time);
+    let recent = now.duration_since(time).is_ok_and(|age| age.as_secs() < 183 * 24 * 60 * 60);
+    if recent{
+        format!("{} {:2} {:02}:{:02}", MONTHS[month - 1], day, hour, minute)
+    }
+    else{
+        format!("{} {:2}  {}", MONTHS[month - 1], day, year)
+    }
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
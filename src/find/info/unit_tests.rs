@@ -0,0 +1,48 @@
+use std::{fs, time::{Duration, UNIX_EPOCH}};
+use crate::find::info;
+
+#[test]
+fn ctime_test(){
+    assert_eq!("Thu Jan  1 00:00:00 1970", info::ctime(UNIX_EPOCH));
+    assert_eq!("Tue Feb 29 12:30:15 2000", info::ctime(UNIX_EPOCH + Duration::from_secs(951827415)));
+    assert_eq!("Sat Oct 18 06:57:00 2025", info::ctime(UNIX_EPOCH + Duration::from_secs(1760770620)));
+    assert_eq!("Wed Dec 31 23:59:59 1969", info::ctime(UNIX_EPOCH - Duration::from_secs(1)));
+}
+
+#[test]
+fn ls_time_test(){
+    let time = UNIX_EPOCH + Duration::from_secs(1760770620); // Oct 18 2025 06:57
+    // Recent times show the hour
+    assert_eq!("Oct 18 06:57", info::ls_time(time, time + Duration::from_secs(60 * 60 * 24)));
+    // Old times or times in the future show the year
+    assert_eq!("Oct 18  2025", info::ls_time(time, time + Duration::from_secs(60 * 60 * 24 * 365)));
+    assert_eq!("Oct 18  2025", info::ls_time(time, time - Duration::from_secs(60)));
+}
+
+#[test]
+fn symbolic_permissions_test(){
+    let dir = fs::symlink_metadata(".").unwrap().file_type();
+    let file = fs::symlink_metadata("Cargo.toml").unwrap().file_type();
+    assert_eq!("drwxr-xr-x", info::symbolic_permissions(dir, 0o755));
+    assert_eq!("-rw-r-----", info::symbolic_permissions(file, 0o640));
+    assert_eq!("-rwsr-Sr-t", info::symbolic_permissions(file, 0o7745));
+    assert_eq!("----------", info::symbolic_permissions(file, 0));
+}
+
+#[test]
+fn lookup_test(){
+    // Build a database like /etc/passwd
+    let mut database = std::env::temp_dir();
+    database.push(format!("amazing_clu_lookup_test_{}", std::process::id()));
+    fs::write(&database, "root:x:0:0:root:/root:/bin/bash\nclu:x:1000:1000::/home/clu:/bin/sh\n").unwrap();
+    let database = database.to_str().unwrap().to_string();
+
+    assert_eq!(Some(1000), info::lookup_id(&database, "clu"));
+    assert_eq!(Some(0), info::lookup_id(&database, "root"));
+    assert_eq!(None, info::lookup_id(&database, "unknown"));
+    assert_eq!("clu", info::lookup_name(&database, 1000));
+    // Unknown ids are written as they are
+    assert_eq!("1234", info::lookup_name(&database, 1234));
+
+    fs::remove_file(&database).unwrap();
+}
\ No newline at end of file
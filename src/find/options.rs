@@ -1,9 +1,8 @@
 use clap::Args;
 
 #[derive(Args)]
-#[group(multiple = false)]
 pub struct Options{
-    // Set this flag on to perform a research by name.
-    #[arg(long)]
-    pub name: bool
+    /// The expression every item must satisfy to be found, written after the paths like in GNU find. Its tests are -name PATTERN and -iname PATTERN (glob on the name), -path PATTERN and -ipath PATTERN (glob on the whole path), -regex REGEX and -iregex REGEX (regex on the whole path), -type f|d|l, -size [+-]N[cwbkMG], -empty, -newer FILE, -mtime [+-]DAYS, -mmin [+-]MINUTES, -perm [-/]OCTAL_MODE, -user NAME|UID and -group NAME|GID. They're combined with ! or -not, -a or -and (the default when nothing is written between two tests), -o or -or and parentheses, which must be escaped in most shells: \( and \). Example: find . -name '*.rs' -o \( -type d -empty \). The expression may also contain actions: -print, -print0, -printf FORMAT (with %p path, %f name, %h directory, %s size, %d depth, %m and %M permissions, %t and %T@ modification time in UTC, %u, %g, %U and %G owner, %y type and %l link target), -exec COMMAND ; (where {} is replaced by the path), -exec COMMAND {} + (running the command for batches of items), -delete, -ls and -prune (skipping the contents of a directory). Without actions other than -prune, the items satisfying the expression are printed. Finally, the global options -maxdepth N, -mindepth N, -depth (the contents of every directory before the directory itself) and -xdev (the same as --one-file-system) may be written anywhere in it.
+    #[arg(last = true, allow_hyphen_values = true, value_name = "EXPRESSION")]
+    pub predicates: Vec<String>
 }
\ No newline at end of file
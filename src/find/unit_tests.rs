@@ -1,47 +1,102 @@
-use crate::{find::{Find, options::Options}, clu_errors::CluErrors};
+use std::path::PathBuf;
+use crate::{case::{Case, Folding}, walker::{Traversal, Symlinks}, find::{Find, options::Options}, clu_errors::CluErrors};
 
 #[test]
-fn filter_by_name_test(){
-    let parsed = vec!["sample.rs".to_string(), "sample.txt".to_string(), "sample2.txt".to_string(), "sample.py".to_string()]; // simulated parsed path passed to the function
-
+fn name_regex_test(){
+    let parsed = ["sample.rs", "sample.txt", "sample2.txt", "sample.py"]; // simulated paths found by the walker
+    let matching = |find: &Find| -> Vec<&str>{
+        let regex = find.name_regex().unwrap();
+        parsed.into_iter().filter(|path| regex.is_match(path.as_bytes())).collect()
+    };
 
     // Find a concrete file
     let find = Find{
         from: vec!["".to_string()], // Not important for this test
-        expression: Some(Box::new("sample.rs".to_string())),
-        ignore_case: false,
+        expression: Some("sample.rs".to_string()),
+        case: Case{ ignore_case: false, smart_case: false, no_ignore_case: false, case_folding: Folding::Unicode },
         hidden_items: false,  // Not important for this test
-        options: Options{name: true} // Not important for this test
+        threads: 1,  // Not important for this test
+        traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false },
+        no_messages: false,  // Not important for this test
+        yes: false,  // Not important for this test
+        json: false,
+        options: Options{ predicates: Vec::new() } // Not important for this test
     };
-    assert_eq!(vec!["sample.rs".to_string()], find.filter_by_name(parsed.clone()).unwrap());
+    assert_eq!(vec!["sample.rs"], matching(&find));
 
     // Find files using a pattern
     let find = Find{
         from: vec!["".to_string()], // Not important for this test
-        expression: Some(Box::new("*.txt".to_string())),
-        ignore_case: false,
+        expression: Some("*.txt".to_string()),
+        case: Case{ ignore_case: false, smart_case: false, no_ignore_case: false, case_folding: Folding::Unicode },
         hidden_items: false,  // Not important for this test
-        options: Options{name: true} // Not important for this test
+        threads: 1,  // Not important for this test
+        traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false },
+        no_messages: false,  // Not important for this test
+        yes: false,  // Not important for this test
+        json: false,
+        options: Options{ predicates: Vec::new() } // Not important for this test
     };
-    assert_eq!(vec!["sample.txt".to_string(), "sample2.txt".to_string()], find.filter_by_name(parsed.clone()).unwrap());
+    assert_eq!(vec!["sample.txt", "sample2.txt"], matching(&find));
 
     // Find everything if no pattern specified
     let find = Find{
         from: vec!["".to_string()], // Not important for this test
         expression: None,
-        ignore_case: false,
+        case: Case{ ignore_case: false, smart_case: false, no_ignore_case: false, case_folding: Folding::Unicode },
         hidden_items: false,  // Not important for this test
-        options: Options{name: true} // Not important for this test
+        threads: 1,  // Not important for this test
+        traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false },
+        no_messages: false,  // Not important for this test
+        yes: false,  // Not important for this test
+        json: false,
+        options: Options{ predicates: Vec::new() } // Not important for this test
     };
-    assert_eq!(parsed, find.filter_by_name(parsed.clone()).unwrap());
+    assert_eq!(parsed.to_vec(), matching(&find));
 
     // Regex error
     let find = Find{
         from: vec!["".to_string()], // Not important for this test
-        expression: Some(Box::new("[a-z".to_string())),
-        ignore_case: false,
+        expression: Some("[a-z".to_string()),
+        case: Case{ ignore_case: false, smart_case: false, no_ignore_case: false, case_folding: Folding::Unicode },
         hidden_items: false,  // Not important for this test
-        options: Options{name: true} // Not important for this test
+        threads: 1,  // Not important for this test
+        traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false },
+        no_messages: false,  // Not important for this test
+        yes: false,  // Not important for this test
+        json: false,
+        options: Options{ predicates: Vec::new() } // Not important for this test
+    };
+    assert_eq!(CluErrors::RegexError("[a-z".to_string()), find.name_regex().unwrap_err());
+}
+
+#[test]
+fn contents_first_test(){
+    // Path to the test folder
+    let mut path = PathBuf::new();
+    path.push(".");
+    path.push("tests");
+    path.push("find_files");
+    let root = path.to_str().unwrap().to_string();
+
+    // With -depth, every directory goes after its contents, and the siblings keep their order. The last items of the walk are its deepest directory and its ancestors, which are only written once the walk ends
+    let find = Find{
+        from: vec![root.clone()],
+        expression: None,
+        case: Case{ ignore_case: false, smart_case: false, no_ignore_case: false, case_folding: Folding::Unicode },
+        hidden_items: false,
+        threads: 4,
+        traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false },
+        no_messages: false,
+        yes: false,
+        json: false,
+        options: Options{ predicates: vec!["-depth".to_string()] }
     };
-    assert_eq!(CluErrors::RegexError("[a-z".to_string()), find.filter_by_name(parsed.clone()).unwrap_err());
+    let mut out = Vec::new();
+    assert!(!find.execute(&mut out).unwrap().errors_occurred);
+    let expected: Vec<String> = ["found_file1.txt", "found_html.html", "subfolder1/found_file2.txt", "subfolder1/subfolder2/found_file3.txt", "subfolder1/subfolder2", "subfolder1", ""]
+        .iter()
+        .map(|item| if item.is_empty(){ root.clone() } else{ path.join(item).to_str().unwrap().to_string() })
+        .collect();
+    assert_eq!(expected, String::from_utf8(out).unwrap().lines().collect::<Vec<&str>>());
 }
\ No newline at end of file
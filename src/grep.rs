@@ -1,32 +1,102 @@
 use clap::Args;
-use regex::{Regex,RegexBuilder};
-use std::{fs,collections::HashMap, path::Path, thread::Builder, sync::mpsc};
+use regex::bytes::Regex;
+use std::{env, fs::File, io::{self, BufRead, BufReader, Write}, collections::{BTreeMap, VecDeque}, path::Path, slice, sync::mpsc, thread, time::Instant};
 
 mod context;
 mod options;
 mod counters;
-use crate::{base, clu_errors::CluErrors, grep::{context::Context, options::Options, counters::Counters}};
+mod binary;
+mod color;
+mod output;
+mod patterns;
+mod multiline;
+mod replace;
+mod limits;
+use crate::{Execution, base::{self, SortBy}, case::Case, clu_errors::CluErrors, pool::{self, Turns}, walker::{Walker, Traversal, glob::Glob}, grep::{context::Context, options::Options, counters::Counters, binary::{Binary, BinaryFiles}, color::{Color, Palette}, output::{Output, Format, FileOutput, Position, Stats, Totals}, patterns::Patterns, multiline::Multiline, replace::{Replace, Match}, limits::{Limits, Budget}}};
+
+// Path used to ask grep to read from the standard input, and the name used to refer to it in the output
+const STDIN_PATH: &str = "-";
+const STDIN_NAME: &str = "(standard input)";
+// How many bytes of the lines found in a regular file are gathered before sending them to be written. The lines found in the standard input or in a pipe are sent one by one, as the next ones may take a while to arrive
+const BATCH_SIZE: usize = 8 * 1024;
+// How many files found by the walk of -R may be waiting to be searched before the walk waits for the search
+const QUEUED_FILES: usize = 1024;
+
+// How the contents of every file are read: what to do if they're binary, and whether they're matched at once in multiline mode
+#[derive(Clone, Copy)]
+struct Reading{
+    binary_files: BinaryFiles,
+    multiline: bool
+}
+
+// A piece of the results of a file, sent by the worker searching it as soon as it's found: some lines to be written, or the end of the file with whether any line was selected in it or the error that stopped its search
+enum Chunk{
+    Lines(String),
+    End(Result<bool, CluErrors>)
+}
 
 #[derive(Args)]
+#[command(disable_help_flag = true)] // -h is --no-filename, as in GNU grep
 pub struct Grep{
-    /// Specify the pattern to use in your search with this argument. If you use a regular expresion, wrap it with "".
-    pattern: Box<String>,
+    /// Specify the pattern to use in your search with this argument. If you use a regular expresion, wrap it with "". If patterns are given with -e or -f, this argument is taken as the first path instead.
+    #[arg(required_unless_present_any = ["regexp", "file"])]
+    pattern: Option<String>,
 
-    /// Specify the path to the file where you want to perform the search. 
+    /// Specify the path to the file where you want to perform the search. If no path is given, or the path is -, grep reads from the standard input, so it can be used in a pipeline.
     path: Vec<String>, // It must be a Vec<String> in order to accept automatically globbed paths (if globbing takes place)
 
-    /// Set this flag on if your path is a directory and you want to check within all the files inside the directory and its subdirectories
-    #[arg(short = 'R', long)]
+    /// Set this flag on if your path is a directory and you want to check within all the files inside the directory and its subdirectories. As in GNU grep, -r and --recursive do the same.
+    #[arg(short = 'R', long, visible_short_alias = 'r', visible_alias = "recursive")]
     dereference_recursive: bool,
 
-    /// Set this flag on to make the search pattern case insensitive.
-    #[arg(short, long)]
-    ignore_case: bool,
-
-    /// By default, grep ignores the hidden files and directories (those starting with .). Set this flag on to explicitly search inside of them. Note: If you're looking for hidden files with a determined extension, adding to the path *.txt -H may not be enough if your shell carries out globbing automatically. If that's the case, wrap the pattern into single quotes to ensure the globbing is carried out by the utility instead of by the shell.
-    #[arg(short = 'H', long)]
+    /// By default, grep ignores the hidden files and directories (those starting with .). Set this flag on to explicitly search inside of them. Note: If you're looking for hidden files with a determined extension, adding to the path *.txt --hidden-items may not be enough if your shell carries out globbing automatically. If that's the case, wrap the pattern into single quotes to ensure the globbing is carried out by the utility instead of by the shell.
+    #[arg(long)]
     hidden_items:bool,
 
+    /// Set the maximum number of threads used to explore the directories and to search in the files. By default, one per available core.
+    #[arg(short = 'j', long, default_value_t = pool::default_threads())]
+    threads: usize,
+
+    /// Set the order in which the results of several files are shown. By default (none), they're shown in the order of the arguments, traversing the directories in alphabetical order. With path, they're sorted by their path, and with mtime, they're sorted from the oldest to the newest modified file. Anyway, the files are still searched concurrently.
+    #[arg(long, value_enum, value_name = "SORTBY", default_value_t = SortBy::None)]
+    sort: SortBy,
+
+    /// Set this flag on to suppress the error messages about files or directories that cannot be read. Grep doesn't stop if it finds one of them, it reports the error and keeps searching in the rest of them. Note that the exit status still reflects that an error occurred.
+    #[arg(short = 's', long)]
+    no_messages: bool,
+
+    // Patterns
+    #[command(flatten)]
+    patterns: Patterns,
+
+    // Case
+    #[command(flatten)]
+    case: Case,
+
+    // Traversal
+    #[command(flatten)]
+    traversal: Traversal,
+
+    // Binary files
+    #[command(flatten)]
+    binary: Binary,
+
+    // Colors
+    #[command(flatten)]
+    color: Color,
+
+    // Output format
+    #[command(flatten)]
+    output: Output,
+
+    // Replacements
+    #[command(flatten)]
+    replace: Replace,
+
+    // Limits
+    #[command(flatten)]
+    limits: Limits,
+
     // Context
     #[command(flatten)]
     context: Context,
@@ -42,280 +112,503 @@ pub struct Grep{
 
 impl Grep{
 
-    pub fn execute(mut self) -> Result<String, CluErrors>
+    // terminal tells if out is a terminal, in which case --color=auto colors the output
+    pub fn execute(mut self, out: &mut dyn Write, terminal: bool) -> Result<Execution, CluErrors>
     {
-        // Check if the introduced command combination is valid
-        if !self.validate_commands(){ 
-            return Err(CluErrors::InvalidCommandCombination(String::from("grep"))) 
+        // The files or directories that cannot be read are collected here and reported at the end, without stopping the search
+        let mut errors = Vec::new();
+        // With -e or -f, there's no pattern among the positional arguments, so the first one is a path
+        if !self.patterns.is_empty(){
+            self.path.splice(0..0, self.pattern.take());
         }
-        let mut path = if self.path.len() > 1{
-            std::mem::replace(&mut self.path, Vec::new()) // As self.path is not required to be part of self after assigning it here, we take it out and hold it in path, leaving an empty Vector in self. Then, the contents of path can be safely spawned among threads without having to export self with them.
+        let paths = if self.path.is_empty(){
+            vec![STDIN_PATH.to_string()] // Without a path, grep reads from the standard input
         }
         else{
-            base::parse_path(&self.path[0], false, self.hidden_items)? // If globbing hasn't taken place, we manually do it through the parse_path function
+            std::mem::take(&mut self.path) // As self.path is not required to be part of self after assigning it here, we take it out and hold it in paths, leaving an empty Vector in self.
         };
-
-        if self.dereference_recursive{
-            path = base::parse_path_recursively(&path, self.hidden_items)?;
+        // The standard input isn't part of any directory tree, so it's kept apart while the rest of the paths are expanded. If globbing hasn't taken place, the walker manually does it
+        // The names are decided from the operands the user gave, before any of them is expanded or dropped for being unreadable
+        let several_files = paths.len() > 1 || paths.iter().any(|path| Glob::has_wildcards(path));
+        let (mut path, files): (Vec<String>, Vec<String>) = paths.into_iter().partition(|item| item == STDIN_PATH);
+        let walker = Walker{ hidden_items: self.hidden_items, traversal: self.traversal, threads: self.threads };
+        // The results are written as soon as they're found. As in POSIX grep, the search only succeeds if any line is selected, even if the counts of 0 have been written
+        let found = if self.dereference_recursive && self.sort == SortBy::None{
+            // Every file is searched as soon as the walk finds it, while the walk goes on in its own thread
+            thread::scope(|scope| -> Result<bool, CluErrors>{
+                let (sender, receiver) = mpsc::sync_channel(QUEUED_FILES);
+                let walk = scope.spawn(move ||{
+                    let mut walk_errors = Vec::new();
+                    // Once the search stops, the receiver is dropped and so is the walk
+                    let walked = walker.walk_pruning(&files, &mut walk_errors, |_entry| false, |entry| !entry.file_type.is_file() || sender.send(entry.path).is_ok());
+                    (walked, walk_errors)
+                });
+                let found = self.execute_multiple_files_grep(path.into_iter().chain(receiver), several_files, terminal, out, &mut errors);
+                let (walked, walk_errors) = walk.join().map_err(|_err| CluErrors::UnexpectedError)?;
+                errors.splice(0..0, walk_errors);
+                walked?;
+                found
+            })?
         }
-
-        // Gets the request and converts it into a single String to be printed
-        let search = self.execute_multiple_files_grep(path)?
-            .join("\n")
-            .trim_end_matches("\n")
-            .trim_start_matches("\n")
-            .to_string();
-        if search.is_empty(){ return Err(CluErrors::NotFoundError);}
+        else{
+            if self.dereference_recursive{
+                path.extend(
+                    walker.walk(&files, &mut errors)?
+                        .into_iter()
+                        .filter(|entry| entry.file_type.is_file())
+                        .map(|entry| entry.path)
+                );
+            }
+            else{
+                path.append(&mut walker.expand(&files, &mut errors));
+            }
+            // Sorting needs the whole list of files before searching any of them
+            base::sort_paths(&mut path, self.sort);
+            self.execute_multiple_files_grep(path, several_files, terminal, out, &mut errors)?
+        };
+        base::report_errors(&errors, self.no_messages);
+        if !found && errors.is_empty(){ return Err(CluErrors::NotFoundError);}
+        // With -q, finding a line is enough to succeed, as in GNU grep
         Ok(
-            search
+            Execution{ errors_occurred: !(errors.is_empty() || self.limits.quiet && found) }
         )
     }
 
     // This function executes the desired action in each single file. There's so many inputs that may be passed just using self right? Check the next function out to find out the reason!
     fn execute_single_file_grep(
         reg: Regex,
-        file: &String, 
-        before_context: usize, 
-        after_context: usize, 
-        context: usize, 
-        files_with_matches: bool,
-        line_number: bool, 
-        invert_match:bool, 
-        only_matching: bool, 
-        count: bool, 
-        total_count: bool
-    ) -> Result<Vec<String>, CluErrors>{
+        file: &str,
+        context: Context,
+        options: Options,
+        counters: Counters,
+        reading: Reading,
+        output: &mut FileOutput
+    ) -> Result<bool, CluErrors>{
+        let format = output.format;
         if Path::new(&file).is_dir(){
             return Err(CluErrors::InputError(format!("{} is dir. If you want to use grep recursively in a directory, add the -R flag. For more information try --help", file)));
         }
-        let contents = fs::read_to_string(&file).map_err(|_error| CluErrors::InputError(format!("{} cannot be read",file)))?;
+        // The file is read through a buffer, so its contents are never loaded in memory at once (except in multiline mode). If a read fails in the middle of the file, we report it as if the file couldn't be opened
+        let cannot_be_read = |_error: io::Error| CluErrors::InputError(format!("{} cannot be read", Self::display_name(file)));
+        let mut contents: Box<dyn BufRead> = if file == STDIN_PATH{
+            Box::new(io::stdin().lock())
+        }
+        else{
+            Box::new(BufReader::new(File::open(file).map_err(cannot_be_read)?))
+        };
+        let is_binary = reading.binary_files != BinaryFiles::Text && Self::is_binary(&mut contents).map_err(cannot_be_read)?;
+        if is_binary && reading.binary_files == BinaryFiles::WithoutMatch{
+            return Ok(false);
+        }
+        // With --in-place, the matches are replaced in the file instead of being written
+        if let Some(replace) = format.replace.as_ref().filter(|replace| replace.in_place){
+            if file == STDIN_PATH{
+                return Err(CluErrors::InputError("The standard input cannot be edited in place".to_string()));
+            }
+            if is_binary{
+                return Ok(false);
+            }
+            let mut read = Vec::new();
+            contents.read_to_end(&mut read).map_err(cannot_be_read)?;
+            let matches = if reading.multiline{ replace::find(&reg, &read, replace.template()) } else{ replace::find_in_lines(&reg, &read, replace.template()) };
+            replace.edit(file, &read, &matches)?.into_iter().for_each(|line| output.write(line));
+            return Ok(!matches.is_empty());
+        }
+        // In multiline mode, the patterns may match across lines, so the whole file is read and matched at once
+        let multiline = if reading.multiline{ Some(Multiline::read(&reg, &mut contents, format.template()).map_err(cannot_be_read)?) } else{ None };
+        // With -m 0, no line can be selected, so no file matches
+        let files_with_matches = |reg, contents| -> io::Result<bool>{
+            let matched = match &multiline{
+                Some(multiline) => multiline.files_with_matches(options.invert_match),
+                None => Self::files_with_matches(reg, contents, options.invert_match)?
+            };
+            Ok(matched && format.max_count != Some(0))
+        };
 
-        // Return a  Vec<String> containing all the Strings to be printed. We compute using the appropriate function depending on user's input
-        if count{
-            Ok(Self::count(reg, contents))
+        // Write the lines to be printed into output. We compute them using the appropriate function depending on user's input, and return whether the file has any line selected (or, with -L, whether it's listed), which sets the exit status
+        // The counts are prefixed with the name of their file, as in file:count. With -l, the files without anything to count are skipped
+        if counters.count || counters.total_count{
+            let count = match &multiline{
+                Some(multiline) if counters.count => multiline.count(options.invert_match, format.max_count),
+                Some(multiline) => multiline.total_count(format.max_count),
+                None if counters.count => Self::count(reg, contents, options.invert_match, format.max_count),
+                None => Self::total_count(reg, contents, format.max_count)
+            }.map_err(cannot_be_read)?;
+            // The counts of 0 are still written, although nothing has been selected
+            if !options.files_with_matches || count > 0{
+                output.write(format!("{}{}", format.prefix(output.name, Position::default(), false), count));
+            }
+            Ok(count > 0)
         }
-        else if total_count{
-            Ok(Self::total_count(reg, contents))
+        // Every file listed with -l or -L takes a line from the --max-total budget
+        else if options.files_with_matches || options.files_without_match{
+            let listed = files_with_matches(reg, contents).map_err(cannot_be_read)? != options.files_without_match && format.budget.take();
+            if listed{
+                output.write(format.file_name(output.name));
+            }
+            Ok(listed)
         }
-        else if files_with_matches{
-            if Self::files_with_matches(reg, contents, invert_match){
-                Ok(vec![file.clone()])
+        else if is_binary{ // Printing the lines of a binary file would mess the terminal up, so we just report if it matches
+            let matched = files_with_matches(reg, contents).map_err(cannot_be_read)? && format.budget.take();
+            if format.json{
+                format.totals.add(Stats::default(), matched);
             }
-            else{
-                Ok(vec![])
+            if matched && format.json{
+                output.write(format.json_binary(output.name));
             }
+            else if matched{
+                output.write(format!("Binary file {} matches", output.name));
+            }
+            Ok(matched)
+        }
+        else if let Some(multiline) = &multiline{
+            Self::search_multiline(multiline, context, options, output).map_err(cannot_be_read)
         }
         else{
-            Ok(Self::search(reg, contents, before_context, after_context, context, line_number, invert_match, only_matching))
+            Self::search(reg, contents, context, options, output).map_err(cannot_be_read)
         }
     }
 
-    // This function is call when the path of files to be explored is known to perform the grep action in each of them concurrently
+    // This function is call with the paths of the files to be explored to perform the grep action in each of them concurrently. The paths may still be being found (eg, by a walk) while the first files are searched. The results of every file are written into out as soon as the ones of the previous files have been written, and it returns whether any line was selected (or any file listed with -L), no matter if anything was written for it (eg, the counts of 0). The errors found in a single file are pushed into errors, so the results found in the rest of files aren't discarded. An error writing the output stops the whole search instead. No matter which thread finishes first, the results are written in the same order as the paths
     fn execute_multiple_files_grep(
         &self,
-        paths: Vec<String>
-    ) -> Result<Vec<String>, CluErrors>{
-
-        let mut search = Vec::new();
-
-        // These arguments are going to be spawned into threads in order to call the required functions for each file. As all of them are copy arguments, we make here a copy of each of them in order to send it among the threads. Why not passing self to the functions? Well, as self cannot implement the copy trait due to its pattern and path arguments, passing self to functions that will be called inside threads means that we have to handle how to pass self through the threads. An Arc, Mutex construction might be a solution, however, as our threads only mission is to call other functions, the MutexGuard would be locked until the end of each thread and then there's no concurrency. In this way, defining function that don't depend on self but on its copy arguments, we can spawn the threads more efficiently.
-        let before_context = self.context.before_context;
-        let after_context = self.context.after_context;
-        let context = self.context.context;
-        let files_with_matches = self.options.files_with_matches;
-        let line_number = self.options.line_number;
-        let invert_match = self.options.invert_match;
-        let only_matching = self.options.only_matching;
-        let count = self.counters.count;
-        let total_count = self.counters.total_count;
-
-        let mut handles = Vec::new();
-        let (tx, rx) = mpsc::channel();
-        for file in paths{
-            let tx1 = tx.clone();
-            // Build the regex. We build one for each thread as otherwise we need to use an Arc,Mutex construction, however, as reg is used in the Grep functions, the Mutex'd be locked until the end of the execution in each thread, so the only concurrent part would be the send of the message which isn't good enough. 
-            let reg = RegexBuilder::new(self.pattern.as_str())
-                .case_insensitive(self.ignore_case)
-                .build()
-                .map_err(|_err| CluErrors::RegexError(self.pattern.to_string()))?;
-            handles.push(Builder::new().spawn(move || -> Result<(), CluErrors>{
-                let mut call = Self::execute_single_file_grep(reg, &file, before_context, after_context, context, files_with_matches, line_number, invert_match, only_matching, count, total_count)?;
-                if call.is_empty(){ // This is not an error, the search is just empty for this file but it can be successful somewhere else
-                    return Ok(());
+        paths: impl IntoIterator<Item = String, IntoIter: Send>,
+        several_files: bool,
+        terminal: bool,
+        out: &mut dyn Write,
+        errors: &mut Vec<CluErrors>
+    ) -> Result<bool, CluErrors>{
+
+        // These argument groups are going to be copied into the workers in order to call the required functions for each file. Why not passing self to the functions? Well, defining functions that don't depend on self but on its copy arguments, we don't have to care about how self is shared among the workers, and these functions can be easily tested on their own.
+        let context = self.context;
+        // The columns are written after the line numbers, so --column and --vimgrep imply -n
+        let vimgrep = self.output.vimgrep;
+        let column = self.output.column || vimgrep;
+        let options = Options{ line_number: self.options.line_number || column, ..self.options };
+        // With -q, nothing is counted, we just look for a line to select
+        let quiet = self.limits.quiet;
+        let counters = if quiet{ Counters{ count: false, total_count: false } } else{ self.counters };
+        let reading = Reading{ binary_files: self.binary.mode(), multiline: self.patterns.multiline };
+        // Like in GNU grep, the names of the files are shown if there may be several of them, the groups of context lines are separated and the colors are only used by default if the user is watching the output
+        // The counts of -c -l are listed as the files of -l, so they're prefixed with their names even if there's a single file
+        let listed_counts = options.files_with_matches && (counters.count || counters.total_count);
+        let with_filename = self.output.with_filename || vimgrep || (!self.output.no_filename && (several_files || self.dereference_recursive || listed_counts));
+        let grouped = context.before() + context.after() > 0 && !options.files_with_matches && !options.files_without_match && !counters.count && !counters.total_count;
+        // The JSON output is never colored nor grouped, every line is an object on its own
+        let json = self.output.json;
+        let format = Format{
+            with_filename,
+            group_separator: (grouped && !self.output.no_group_separator && !json).then(|| self.output.group_separator.clone()),
+            palette: if json{ Palette::plain() } else{ self.color.palette(terminal, env::var("GREP_COLORS").ok().as_deref()) },
+            json,
+            replace: self.replace.replace.is_some().then(|| self.replace.clone()),
+            totals: Totals::default(),
+            max_count: if quiet{ Some(1) } else{ self.limits.max_count },
+            budget: Budget::new(self.limits.max_total),
+            byte_offset: self.output.byte_offset,
+            column,
+            vimgrep
+        };
+        // The lines of --max-total are taken by the first files, so they're searched one after another, in the order their results are written
+        let threads = if self.limits.max_total.is_some(){ 1 } else{ self.threads };
+        let start = Instant::now();
+
+        // Build the regex just once, with every pattern. Cloning a Regex is cheap as the compiled regex is shared among the clones, so each file gets its own clone
+        let patterns = self.patterns.collect(self.pattern.as_deref())?;
+        let reg = self.patterns.regex(&patterns, &self.case)?;
+
+        // The files are searched by a pool of workers, and the lines are sent as soon as they're found. The lines of the file whose turn it is are written straight away, while those of the following files wait here until every previous file has been written
+        let mut written = false;
+        let mut found = false;
+        let mut write_error = None;
+        let mut waiting: BTreeMap<usize, Vec<Chunk>> = BTreeMap::new();
+        let mut next = 0;
+        let mut next_started = false; // Whether anything of the file whose turn it is has been written
+        // A file is only searched while it's less than threads files after the one whose turn it is, so the lines waiting for a slow file don't pile up
+        let turns = Turns::new(threads);
+        pool::run(threads, paths.into_iter().enumerate(), |(position, file), worker| {
+            let _turn = turns.wait(position);
+            // Once the --max-total budget is exhausted, the rest of the files have nothing to write
+            if format.budget.is_exhausted(){
+                return (position, Chunk::End(Ok(false)));
+            }
+            let batch = if Path::new(&file).is_file(){ BATCH_SIZE } else{ 0 };
+            let mut emit = |lines: String| worker.send((position, Chunk::Lines(lines)));
+            let mut output = FileOutput::new(Self::display_name(&file), &format, batch, &mut emit);
+            let result = Self::execute_single_file_grep(reg.clone(), &file, context, options, counters, reading, &mut output); // An empty search is not an error, it can be successful somewhere else
+            output.flush();
+            (position, Chunk::End(result))
+        }, |(position, chunk)| {
+            // With -q, nothing is written, so the first line found ends the search no matter the position of its file, cancelling the workers still searching the rest of them
+            if quiet && matches!(chunk, Chunk::Lines(_)){
+                found = true;
+                turns.release();
+                return false;
+            }
+            if position != next{
+                waiting.entry(position).or_default().push(chunk);
+                return true;
+            }
+            let mut chunks = vec![chunk];
+            loop{
+                let mut ended = false;
+                for chunk in chunks{
+                    match chunk{
+                        Chunk::Lines(lines) => {
+                            // The context lines of different files are never next to each other
+                            let separator = format.separator().filter(|_separator| written && !next_started).map(|separator| separator + "\n").unwrap_or_default();
+                            if let Err(error) = write!(out, "{}{}", separator, lines){
+                                write_error = Some(CluErrors::output(error));
+                                turns.release();
+                                return false;
+                            }
+                            written = true;
+                            next_started = true;
+                        },
+                        Chunk::End(result) => {
+                            match result{
+                                Ok(selected) => found |= selected,
+                                Err(error) => errors.push(error) // Errors from a single file don't stop the execution
+                            }
+                            ended = true;
+                        }
+                    }
+                }
+                if !ended{
+                    return true;
+                }
+                next += 1;
+                next_started = false;
+                turns.advance(next);
+                match waiting.remove(&next){
+                    Some(waiting_chunks) => chunks = waiting_chunks,
+                    None => return true
                 }
-                let mut output = vec![
-                    if !files_with_matches{format!("\n\t----{file}----\n")} else{String::new()}
-                ];
-                output.append(&mut call);
-                tx1.send(output).map_err(|_err|CluErrors::UnexpectedError)?;
-                Ok(())
-            }).map_err(|_err| CluErrors::UnexpectedError)?);
-        }
-        for handle in handles{
-            handle.join().map_err(|_err| CluErrors::UnexpectedError)??; // We handle errors from the thread or from the join
-        }
-        loop{ // The output for the files are pending in the receiver, let's get them!
-            match rx.try_recv(){
-                Ok(mut sent_output) => search.append(&mut sent_output),
-                Err(_) => break
             }
-        }
+        })?;
 
-        Ok(search)
+        // The summary doesn't count as a result, so an unsuccessful search still ends with EXIT_NOT_FOUND
+        if let Some(error) = write_error{
+            return Err(error);
+        }
+        if json{
+            writeln!(out, "{}", format.json_summary(start.elapsed())).map_err(CluErrors::output)?;
+        }
+        Ok(found)
     }
 
 
-    // This function is the core of Grep. It computes the search in so many cases, except if the user requested a count or a files with matches
+    // This function is the core of Grep. It computes the search in so many cases, except if the user requested a count or a files with matches. The contents are consumed line by line, and the only lines kept in memory are the ones that may be printed as before context of a later match, so it's safe to use it in huge files.
     fn search(
         reg: Regex, 
-        contents: String,
-        before_context: usize,
-        after_context: usize,
-        context: usize,
-        line_number_flag: bool,
-        invert_match:bool,
-        only_matching: bool
-    ) -> Vec<String>{
-        // Collect the lines that match the pattern together with its line number into a Vec. Note that if invert_match is selected, the inversion is applied while filtering.
-        let filtered_lines = contents
-            .lines()
-            .enumerate()
-            .filter(|(_line_number, line)| reg.is_match(line) ^invert_match)
-            .collect::<Vec<(usize, &str)>>();
-
-        // We compute after and before, also create a HashMap that will store the lines out of the pattern_match affected by them.
-        let mut context_lines = HashMap::new();
-        let mut before = before_context;
-        let mut after= after_context;
-        if context > 0{
-            before = context;
-            after = context;
-        }
-        let contents_length = contents.lines().count(); // This quantity may be used several times
+        contents: impl BufRead,
+        context: Context,
+        options: Options,
+        output: &mut FileOutput
+    ) -> io::Result<bool>{
+        let template = output.format.template();
+        let find = |_offset: usize, line: &[u8]| replace::find(&reg, line, template);
+        Self::select_lines(contents, |_offset, line| reg.is_match(line), find, context, options, output)
+    }
 
-        // If a context has been defined, we save the lines to use (note that they don't match the pattern as otherwise they'll be shown anyway, so they'll be shown complete even if -o is selected) into the context_lines HashMap.
-        if after > 0 || before > 0{
-            context_lines = Self::context_lines(&contents, &filtered_lines, after, before, contents_length);
-        }
+    // Like search, but the contents have been matched at once in multiline mode, so a line is selected if any match covers it
+    fn search_multiline(
+        multiline: &Multiline,
+        context: Context,
+        options: Options,
+        output: &mut FileOutput
+    ) -> io::Result<bool>{
+        let find = |offset: usize, line: &[u8]| multiline.matches_in(offset, line);
+        Self::select_lines(multiline.contents.as_slice(), |offset, line| !find(offset, line).is_empty(), find, context, options, output)
+    }
 
-        //This is the vector String to output. The starting point are the lines that matched the pattern
-        filtered_lines
-            .iter()
-            .map(|(line_number, line)| { // Create an iterator of Vec<String> containing the lines to be printed. This also includes the context affected lines!
-                let mut output = Vec::new();
-                // Compute this line range affected by the context
-                let lines_before = usize::try_from(*line_number as isize - before as isize).unwrap_or(0);
-                let lines_after = if line_number+after > contents_length-1 {contents_length-1} else {line_number + after};
-                // The context lines must also respect the order, so we push the before lines to the output Vec before pushing the current line. Note that we push each context line just once in the whole program.
-                (lines_before..*line_number)
-                .for_each(|position|{
-                    if context_lines.contains_key(&position){
-                        output.push(Self::output_search_lines(position, context_lines.remove_entry(&position).unwrap().1, true, line_number_flag))
-                    }
-                });
-                // Now push the current line. If -o is activated, we have to push each single ocurrence
-                if only_matching && !invert_match{
-                    reg
-                        .find_iter(line)
-                        .for_each(|matched| 
-                            output.push(Self::output_search_lines(*line_number, matched.as_str(), false, line_number_flag))
-                        );
+    // This function selects the lines of the contents to be written, together with their context. A line is selected if selects is true for it (or false with -v), given its offset in the contents, and find gives the matches inside of it. Every line is written into output as soon as it's known to be printed, and the contents aren't read anymore once output is closed. It returns whether any line has been selected
+    fn select_lines(
+        mut contents: impl BufRead,
+        selects: impl Fn(usize, &[u8]) -> bool,
+        find: impl Fn(usize, &[u8]) -> Vec<Match>,
+        context: Context,
+        options: Options,
+        output: &mut FileOutput
+    ) -> io::Result<bool>{
+        let before = context.before();
+        let after = context.after();
+        let (file, format) = (output.name, output.format);
+
+        // Rolling window holding the last lines that didn't match the pattern, in case the next line matches and they've to be printed as before context
+        let mut before_lines: VecDeque<(usize, usize, Vec<u8>)> = VecDeque::with_capacity(before);
+        // Number of lines still to be printed as after context of the last match
+        let mut pending_after = 0;
+
+        // The matches are only looked for in the lines to be printed if they have to be highlighted, replaced, reported in the JSON output or their columns written
+        let highlights = |offset: usize, line: &[u8]| -> Vec<Match>{
+            if format.palette.highlights_matches() || format.json || format.replace.is_some() || format.column{
+                find(offset, line)
+            }
+            else{
+                Vec::new()
+            }
+        };
+
+        // Every line is written with its prefix, after a separator if it isn't next to the last line written. The text written starts at start in the line (the start of the match with -o), and with --replace, the matches are replaced before writing it. In the JSON output, the lines of a file are preceded by its begin object, which is only written if any line is
+        let mut last_written: Option<usize> = None;
+        let mut write = |output: &mut FileOutput, line_number: usize, offset: usize, start: usize, text: &[u8], matches: &[Match], is_context_line: bool|{
+            if last_written.is_some_and(|last| last + 1 < line_number){
+                if let Some(separator) = format.separator(){
+                    output.write(separator);
+                }
+            }
+            if last_written.is_none() && format.json{
+                output.write(format.json_begin(file));
+            }
+            last_written = Some(line_number);
+            let position = Position{
+                line_number: options.line_number.then_some(line_number),
+                // A selected line without a match (eg, with -v) is written at the column 1, so every selected line has a column to be parsed
+                column: (format.column && !is_context_line).then(|| matches.first().map_or(start, |first| start + first.start)),
+                byte_offset: format.byte_offset.then_some(offset + start)
+            };
+            let (text, matches) = replace::apply(text, matches);
+            if format.json{
+                output.write(format.json_line(file, line_number, offset, &text, &matches, is_context_line));
+            }
+            else{
+                output.write(format.line(file, position, &text, &matches, is_context_line));
+            }
+        };
+
+        let mut stats = Stats::default();
+        let mut line = Vec::new();
+        let mut line_number = 0;
+        let mut offset = 0;
+        // Once the lines allowed by -m have been selected (or the --max-total budget is exhausted), the file is just read to write the after context of the last of them, which ends at the next line that would be selected
+        let mut stopped = format.max_count == Some(0) || format.budget.is_exhausted();
+        loop{
+            if stopped && pending_after == 0 || !output.is_open(){
+                break;
+            }
+            let read = Self::read_line(&mut contents, &mut line)?;
+            if read == 0{
+                break;
+            }
+            // Note that if invert_match is selected, the inversion is applied while filtering.
+            let selected = selects(offset, &line) ^options.invert_match;
+            if selected && (stopped || !format.budget.take()){
+                break;
+            }
+            if selected{
+                stats.matched_lines += 1;
+                // The context lines must also respect the order, so the before lines are pushed to the output Vec before pushing the current line. Note that each context line is pushed just once in the whole file, as it leaves the window when it's printed.
+                before_lines
+                    .drain(..)
+                    .for_each(|(position, line_offset, context_line)| write(output, position, line_offset, 0, &context_line, &highlights(line_offset, &context_line), true));
+                let matches = highlights(offset, &line);
+                stats.matches += matches.len();
+                // Now push the current line. If -o is activated, we have to push each single ocurrence. With --vimgrep, the whole line is pushed once per ocurrence instead. The JSON output already reports every ocurrence of the whole line
+                if options.only_matching && !options.invert_match && !format.json{
+                    find(offset, &line)
+                        .into_iter()
+                        .for_each(|matched|{
+                            let text = matched.replacement.unwrap_or_else(|| line[matched.start..matched.end].to_vec());
+                            write(output, line_number, offset, matched.start, &text, &[Match::new(0, text.len())], false);
+                        });
+                }
+                else if format.vimgrep{
+                    matches
+                        .iter()
+                        .for_each(|matched| write(output, line_number, offset, 0, &line, slice::from_ref(matched), false));
                 }
                 else{
-                    output.push(Self::output_search_lines(*line_number, line, false, line_number_flag));
+                    write(output, line_number, offset, 0, &line, &matches, false);
                 }
-                // Push the lines after.
-                (*line_number..lines_after+1)
-                .for_each(|position|{
-                    if context_lines.contains_key(&position){
-                        output.push(Self::output_search_lines(position, context_lines.remove_entry(&position).unwrap().1, true, line_number_flag))
-                    }
-                });
-                output
-            })
-            .flatten()
-            .collect()
+                pending_after = after;
+                stopped = format.max_count.is_some_and(|max| stats.matched_lines >= max) || format.budget.is_exhausted();
+            }
+            else if pending_after > 0{ // The line is affected by the after context of a previous match (they don't match the pattern as otherwise they'll be shown anyway, so they'll be shown complete even if -o is selected)
+                write(output, line_number, offset, 0, &line, &highlights(offset, &line), true);
+                pending_after -= 1;
+            }
+            else if before > 0{ // The line may be the before context of a following match
+                if before_lines.len() == before{
+                    before_lines.pop_front();
+                }
+                before_lines.push_back((line_number, offset, line.clone()));
+            }
+            line_number += 1;
+            offset += read;
+        }
+
+        // In the JSON output, the lines of a file are followed by its end object
+        if format.json{
+            stats.bytes_searched = offset;
+            format.totals.add(stats, stats.matched_lines > 0);
+            if last_written.is_some(){
+                output.write(format.json_end(file, stats));
+            }
+        }
+        Ok(stats.matched_lines > 0)
     }
 
-    // To call if -c is set. It counts how many lines contain the pattern
-    fn count(reg: Regex, contents: String) -> Vec<String>{
-        vec![
-            contents
-            .lines()
-            .filter(|line| reg.is_match(line))
-            .count()
-            .to_string()
-        ]
+    // To call if -c is set. It counts how many lines contain the pattern (or don't contain it, with -v), up to max_count of them
+    fn count(reg: Regex, mut contents: impl BufRead, invert_match: bool, max_count: Option<usize>) -> io::Result<usize>{
+        let max_count = max_count.unwrap_or(usize::MAX);
+        let mut count: usize = 0;
+        let mut line = Vec::new();
+        while count < max_count && Self::read_line(&mut contents, &mut line)? > 0{
+            if reg.is_match(&line) ^invert_match{
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    // To call if --total-count is set. It contains the number of times the pattern is matched, in the first max_count lines containing it
+    fn total_count(reg: Regex, mut contents: impl BufRead, max_count: Option<usize>) -> io::Result<usize>{
+        let mut lines_left = max_count.unwrap_or(usize::MAX);
+        let mut count: usize = 0;
+        let mut line = Vec::new();
+        while lines_left > 0 && Self::read_line(&mut contents, &mut line)? > 0{
+            let matches = reg.find_iter(&line).count();
+            if matches > 0{
+                count += matches;
+                lines_left -= 1;
+            }
+        }
+        Ok(count)
     }
 
-    // To call if --total-count is set. It contains the number of times the pattern is matched.
-    fn total_count(reg: Regex, contents: String) -> Vec<String>{
-        vec![
-            reg.find_iter(&contents)
-            .count()
-            .to_string()
-        ]
+    // To call if files_with_matches is set, it finds the files containing something that matchs the pattern. The file is read just until the first match is found
+    fn files_with_matches(reg: Regex, mut contents: impl BufRead, invert_match:bool) -> io::Result<bool>{
+        let mut line = Vec::new();
+        while Self::read_line(&mut contents, &mut line)? > 0{
+            if reg.is_match(&line){
+                return Ok(!invert_match);
+            }
+        }
+        Ok(invert_match)
     }
 
-    // To call if files_with_matches is set, it finds the files containing something that matchs the pattern
-    fn files_with_matches(reg: Regex, contents: String, invert_match:bool) -> bool{
-        reg.find(&contents).is_some() ^invert_match
+    // This function detects if the contents belong to a binary file, which happens if there's a NUL byte in their first block. The block is just peeked, so it's still available for the following reads
+    fn is_binary(contents: &mut impl BufRead) -> io::Result<bool>{
+        Ok(contents.fill_buf()?.contains(&0))
     }
 
-    // This function is called by search to determine the HashMap of lines affected by the context
-    fn context_lines<'a>
-    (  
-        contents: &'a str,
-        filtered_lines: &Vec<(usize, &str)>,
-        after: usize,
-        before: usize,
-        contents_length: usize
-    ) -> HashMap<usize, &'a str>{
-        let mut context_lines= HashMap::new();
-        // A starting point are the files that matched the pattern, passed here by the search function
-        filtered_lines
-            .iter()
-            .for_each(|(line_number, _line)|{ 
-                // For each line we find how many lines are before and after that may be affected by context
-                let lines_before = usize::try_from(*line_number as isize - before as isize).unwrap_or(0);
-                let lines_after = if line_number+after > contents_length-1 {contents_length-1} else {line_number + after};
-                // We find the lines affected by the context of the current line:
-                // 1. We ensure they've not been taken in count yet.
-                // 2. We ensure they're not part of the lines that matched the pattern.
-                let lines = (lines_before..lines_after+1)
-                    .filter(|position|
-                        !context_lines.contains_key(position) 
-                        && 
-                        !filtered_lines.iter().any(|(contained, _line)| contained==position)
-                    )
-                    .collect::<Vec<usize>>();
-                // Add the resulting lines to the HashMap
-                contents
-                    .lines()
-                    .enumerate()
-                    .filter(|(line_number, _line)| lines.contains(line_number))
-                    .for_each(|(line_number, line)| {context_lines.insert(line_number, line);});
-            });
-        context_lines
+    // This function reads the next line of contents into line, without its line terminator (\n or \r\n). The line is read as raw bytes, so non UTF-8 contents don't make the read fail and they can be directly matched by the bytes Regex. It returns the number of bytes read, including the line terminator, which is 0 once the contents are exhausted. The same buffer is reused for every line of a file to avoid allocations
+    fn read_line(contents: &mut impl BufRead, line: &mut Vec<u8>) -> io::Result<usize>{
+        line.clear();
+        let read = contents.read_until(b'\n', line)?;
+        if line.ends_with(b"\n"){
+            line.pop();
+            if line.ends_with(b"\r"){
+                line.pop();
+            }
+        }
+        Ok(read)
     }
 
-    // This function is called by search. It just produce a String containing its corresponding line_number if needed
-    fn output_search_lines(line_number: usize, line: &str, is_context_line: bool, line_number_flag: bool) -> String{
-        format!(
-            "{}{}{}", 
-            if line_number_flag{(line_number+1).to_string()} else {"".to_string()}, 
-            if line_number_flag{if is_context_line{"-"} else{":"}}else{""},
-            line
-        )
-    } 
-
-    // This function is called by execute to determine if a command of the group Options has been used in combination with a command of the group Counters. Note that this only works because both are structs composed by bools, then it's enough to check its bytes.
-    fn validate_commands(&self) -> bool{
-        !(
-            base::get_bytes(&self.options).iter().any(|&x| x!=0) 
-            && 
-            base::get_bytes(&self.counters).iter().any(|&x| x!=0)
-        )
+    // This function returns the name used to refer to a file in the output, taking into account that the standard input doesn't have a name on its own
+    fn display_name(file: &str) -> &str{
+        if file == STDIN_PATH{ STDIN_NAME } else{ file }
     }
 
 }
@@ -0,0 +1,26 @@
+use clap::{Args, ValueEnum};
+
+#[derive(Args, Clone, Copy)]
+pub struct Binary{
+    /// Set how binary files (those containing NUL bytes in their first block) are handled. With binary, a single line reporting that the file matches is shown instead of its matching lines. With text, they're searched as any other file. With without-match, they're skipped.
+    #[arg(long, value_enum, value_name = "TYPE", default_value_t = BinaryFiles::Binary)]
+    pub binary_files: BinaryFiles,
+
+    /// Set this flag on to process binary files as if they were text. Equivalent to --binary-files=text.
+    #[arg(short = 'a', long)]
+    pub text: bool
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+pub enum BinaryFiles{
+    Binary,
+    Text,
+    WithoutMatch
+}
+
+impl Binary{
+    // The way to handle binary files, taking into account that -a shadows --binary-files
+    pub fn mode(&self) -> BinaryFiles{
+        if self.text { BinaryFiles::Text } else { self.binary_files }
+    }
+}
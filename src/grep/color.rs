@@ -0,0 +1,112 @@
+use clap::{Args, ValueEnum};
+
+#[derive(Args, Clone, Copy)]
+pub struct Color{
+    /// Set when the output is colored, highlighting the matches, the file names, the line numbers and the separators. The value must be given after an equals sign, eg: --color=always. With auto (the default, also used if the flag is given without a value), only if the standard output is a terminal. The colors can be changed as in GNU grep with the GREP_COLORS environment variable, eg: GREP_COLORS='ms=01;32:fn=34:ln=33'. Its capabilities are mt (every match), ms (matches in selected lines), mc (matches in context lines), sl (selected lines), cx (context lines), fn (file names), ln (line numbers and columns), bn (byte offsets) and se (separators).
+    #[arg(long, alias = "colour", value_enum, value_name = "WHEN", num_args = 0..=1, require_equals = true, default_value_t = ColorWhen::Auto, default_missing_value = "auto")]
+    pub color: ColorWhen
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+pub enum ColorWhen{
+    Auto,
+    Always,
+    Never
+}
+
+// The SGR parameters used to color every part of the output, eg: 01;31 for bold red. An empty one leaves its part uncolored
+#[derive(Default, Debug, PartialEq)]
+pub struct Palette{
+    pub selected_match: String, // ms
+    pub context_match: String, // mc
+    pub selected_line: String, // sl
+    pub context_line: String, // cx
+    pub file_name: String, // fn
+    pub line_number: String, // ln
+    pub byte_offset: String, // bn
+    pub separator: String // se
+}
+
+impl Color{
+    // The palette to use, taking into account if the standard output is a terminal and the value of GREP_COLORS
+    pub fn palette(&self, is_terminal: bool, grep_colors: Option<&str>) -> Palette{
+        let colored = match self.color{
+            ColorWhen::Auto => is_terminal,
+            ColorWhen::Always => true,
+            ColorWhen::Never => false
+        };
+        if !colored{
+            return Palette::plain();
+        }
+        let mut palette = Palette::gnu();
+        if let Some(grep_colors) = grep_colors{
+            palette.apply(grep_colors);
+        }
+        palette
+    }
+}
+
+impl Palette{
+    // Nothing is colored
+    pub fn plain() -> Palette{
+        Palette::default()
+    }
+
+    // The default colors of GNU grep
+    fn gnu() -> Palette{
+        Palette{
+            selected_match: "01;31".to_string(),
+            context_match: "01;31".to_string(),
+            selected_line: String::new(),
+            context_line: String::new(),
+            file_name: "35".to_string(),
+            line_number: "32".to_string(),
+            byte_offset: "32".to_string(),
+            separator: "36".to_string()
+        }
+    }
+
+    // Overrides the colors given in a GREP_COLORS value, made of capabilities such as ms=01;31 separated by colons. Like in GNU grep, the unknown capabilities and the invalid values are ignored
+    fn apply(&mut self, grep_colors: &str){
+        for capability in grep_colors.split(':'){
+            let Some((name, value)) = capability.split_once('=') else { continue };
+            if !value.chars().all(|character| character.is_ascii_digit() || character == ';'){
+                continue;
+            }
+            let value = value.to_string();
+            match name{
+                "mt" => {
+                    self.selected_match = value.clone();
+                    self.context_match = value;
+                },
+                "ms" => self.selected_match = value,
+                "mc" => self.context_match = value,
+                "sl" => self.selected_line = value,
+                "cx" => self.context_line = value,
+                "fn" => self.file_name = value,
+                "ln" => self.line_number = value,
+                "bn" => self.byte_offset = value,
+                "se" => self.separator = value,
+                _ => ()
+            }
+        }
+    }
+
+    // Colors the text, erasing up to the end of the line after every sequence as GNU grep does, so the background color doesn't spread
+    pub fn paint(color: &str, text: &str) -> String{
+        if color.is_empty() || text.is_empty(){
+            text.to_string()
+        }
+        else{
+            format!("\x1b[{}m\x1b[K{}\x1b[m\x1b[K", color, text)
+        }
+    }
+
+    // Checks if the matches must be found to be highlighted
+    pub fn highlights_matches(&self) -> bool{
+        !self.selected_match.is_empty() || !self.context_match.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
@@ -0,0 +1,29 @@
+use crate::grep::color::{Color, ColorWhen, Palette};
+
+#[test]
+fn palette_test(){
+    // With auto, the output is only colored in a terminal
+    let auto = Color{ color: ColorWhen::Auto };
+    assert_eq!(Palette::plain(), auto.palette(false, None));
+    assert_eq!(Palette::gnu(), auto.palette(true, None));
+    assert_eq!(Palette::gnu(), Color{ color: ColorWhen::Always }.palette(false, None));
+    assert_eq!(Palette::plain(), Color{ color: ColorWhen::Never }.palette(true, Some("ms=32")));
+
+    // GREP_COLORS overrides the default colors, ignoring what it doesn't understand
+    let palette = auto.palette(true, Some("mt=01;32:fn=:ln=33:bn=34:xx=1:se=red:sl"));
+    assert_eq!("01;32", palette.selected_match);
+    assert_eq!("01;32", palette.context_match);
+    assert_eq!("", palette.file_name);
+    assert_eq!("33", palette.line_number);
+    assert_eq!("34", palette.byte_offset);
+    assert_eq!("36", palette.separator);
+    assert_eq!("", palette.selected_line);
+}
+
+#[test]
+fn paint_test(){
+    assert_eq!("\x1b[01;31m\x1b[Kgrep\x1b[m\x1b[K", Palette::paint("01;31", "grep"));
+    // Nothing to color
+    assert_eq!("grep", Palette::paint("", "grep"));
+    assert_eq!("", Palette::paint("01;31", ""));
+}
\ No newline at end of file
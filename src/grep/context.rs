@@ -1,6 +1,6 @@
 use clap::Args;
 
-#[derive(Args)]
+#[derive(Args, Clone, Copy)]
 #[group(multiple = true)]
 pub struct Context{
     /// Use this argument to show a determined number of lines after the lines where the pattern has been matched. 
@@ -16,4 +16,16 @@ pub struct Context{
     /// Equivalent to set -A and -B to the same number. If -C is set, it'll shadow -A and -B.
     #[arg(short='C', long, default_value_t = 0)]
     pub context: usize,
+}
+
+impl Context{
+    // Lines to show before each match, taking into account that -C shadows -B
+    pub fn before(&self) -> usize{
+        if self.context > 0 { self.context } else { self.before_context }
+    }
+
+    // Lines to show after each match, taking into account that -C shadows -A
+    pub fn after(&self) -> usize{
+        if self.context > 0 { self.context } else { self.after_context }
+    }
 }
\ No newline at end of file
@@ -1,13 +1,13 @@
 use clap::Args;
 
-#[derive(Args)]
-#[group(multiple=false)] // Counters are not compatible with Options, in addition they are not compatible with each other
+#[derive(Args, Clone, Copy)]
+#[group(multiple=false)] // Counters are not compatible with each other
 pub struct Counters{
-    /// Set this flag on to count the number of lines containing the pattern. Not allowed to use it together with -l, -n, -v, -o or --total-count.
-    #[arg(short, long)]
+    /// Set this flag on to count the number of lines containing the pattern (or not containing it, with -v). With -l, the files with a count of 0 are skipped and the counts are prefixed with the name of their file even if there's a single one. Not allowed to use it together with -n, -o or --total-count.
+    #[arg(short, long, conflicts_with_all = ["line_number", "only_matching"])]
     pub count: bool,
 
-    /// Set this flag on to count the number of times the pattern is matched. Not allowed to use it together with -l, -n, -v, -o or -c.
-    #[arg(long)]
+    /// Set this flag on to count the number of times the pattern is matched. With -l, the files with a count of 0 are skipped and the counts are prefixed with the name of their file even if there's a single one. Not allowed to use it together with -n, -v, -o or -c.
+    #[arg(long, conflicts_with_all = ["line_number", "invert_match", "only_matching"])]
     pub total_count: bool,
 }
\ No newline at end of file
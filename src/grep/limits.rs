@@ -0,0 +1,36 @@
+use clap::Args;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Args, Clone, Copy)]
+pub struct Limits{
+    /// Stop reading a file after NUM selected lines (the lines not matching the pattern with -v). The after context of the last of them is still shown. With -c or --total-count, only the matches in those lines are counted.
+    #[arg(short = 'm', long, value_name = "NUM")]
+    pub max_count: Option<usize>,
+
+    /// Stop the whole search after NUM selected lines, taking the files in the order their results are shown (with -l or -L, every file listed takes one). The files are searched one after another instead of concurrently. Not allowed to use it together with -c or --total-count.
+    #[arg(long, value_name = "NUM", conflicts_with_all = ["count", "total_count"])]
+    pub max_total: Option<usize>,
+
+    /// Set this flag on to write nothing in the standard output and stop at the first selected line of any file. The exit status is 0 if a line was selected, even if an error was found, and 1 otherwise.
+    #[arg(short = 'q', long, visible_alias = "silent", conflicts_with = "json")]
+    pub quiet: bool
+}
+
+// The number of lines that may still be selected in the whole search with --max-total, shared among the files. Without --max-total, there's no limit
+#[derive(Default)]
+pub struct Budget(Option<AtomicUsize>);
+
+impl Budget{
+    pub fn new(max_total: Option<usize>) -> Budget{
+        Budget(max_total.map(AtomicUsize::new))
+    }
+
+    // Takes a line from the budget, if there's any left
+    pub fn take(&self) -> bool{
+        self.0.as_ref().is_none_or(|left| left.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| left.checked_sub(1)).is_ok())
+    }
+
+    pub fn is_exhausted(&self) -> bool{
+        self.0.as_ref().is_some_and(|left| left.load(Ordering::SeqCst) == 0)
+    }
+}
\ No newline at end of file
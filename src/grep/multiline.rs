@@ -0,0 +1,86 @@
+use regex::bytes::Regex;
+use std::io::{self, BufRead};
+use crate::grep::{Grep, replace::{self, Match}};
+
+// The contents of a file matched at once in multiline mode, so a match may span several lines. A line is selected if any match covers part of it, its line terminator included
+pub struct Multiline{
+    pub contents: Vec<u8>,
+    matches: Vec<Match> // Every match, in order
+}
+
+impl Multiline{
+    // Reads the whole contents and finds every match in them, with its replacement if a template is given
+    pub fn read(reg: &Regex, mut contents: impl BufRead, template: Option<&[u8]>) -> io::Result<Multiline>{
+        let mut read = Vec::new();
+        contents.read_to_end(&mut read)?;
+        let matches = replace::find(reg, &read, template);
+        Ok(Multiline{ contents: read, matches })
+    }
+
+    // The parts of the matches covering a line, relative to its start, given its offset in the contents. A match covering its line terminator selects the line with an empty part at its end. The replacement of a match is written in the line where it starts, so the parts in the following lines are replaced by nothing
+    pub fn matches_in(&self, offset: usize, line: &[u8]) -> Vec<Match>{
+        let end = offset + line.len();
+        let first = self.matches.partition_point(|matched| matched.end < offset);
+        self.matches[first..]
+            .iter()
+            .take_while(|matched| matched.start <= end)
+            .filter(|matched| matched.end > offset || matched.start == offset)
+            .map(|matched| Match{
+                start: matched.start.max(offset) - offset,
+                end: matched.end.min(end).max(offset) - offset,
+                replacement: matched.replacement.as_ref().map(|replacement| if matched.start >= offset{ replacement.clone() } else{ Vec::new() })
+            })
+            .collect()
+    }
+
+    // To call if -c is set. It counts how many lines are covered by the matches (or aren't covered, with -v), up to max_count of them
+    pub fn count(&self, invert_match: bool, max_count: Option<usize>) -> io::Result<usize>{
+        let mut count = 0;
+        self.for_each_line(|offset, line|{
+            if self.matches_in(offset, line).is_empty() == invert_match{
+                count += 1;
+            }
+        })?;
+        Ok(max_count.map_or(count, |max| count.min(max)))
+    }
+
+    // To call if --total-count is set. Every match is counted once, no matter how many lines it covers. With max_count, only the matches starting in the first max_count lines covered are counted
+    pub fn total_count(&self, max_count: Option<usize>) -> io::Result<usize>{
+        let Some(max_count) = max_count else{
+            return Ok(self.matches.len());
+        };
+        // The end of the last line counted, where a match may still start by covering its line terminator
+        let mut covered = 0;
+        let mut end = None;
+        self.for_each_line(|offset, line|{
+            if covered < max_count && !self.matches_in(offset, line).is_empty(){
+                covered += 1;
+                end = Some(offset + line.len());
+            }
+        })?;
+        Ok(end.map_or(0, |end| self.matches.partition_point(|matched| matched.start <= end)))
+    }
+
+    // To call if files_with_matches is set. As in the line mode, a file matches if the pattern is found in it, and with -v if it isn't
+    pub fn files_with_matches(&self, invert_match: bool) -> bool{
+        !self.matches.is_empty() ^ invert_match
+    }
+
+    // Calls f with the offset and the contents of every line
+    fn for_each_line(&self, mut f: impl FnMut(usize, &[u8])) -> io::Result<()>{
+        let mut contents = self.contents.as_slice();
+        let mut line = Vec::new();
+        let mut offset = 0;
+        loop{
+            let read = Grep::read_line(&mut contents, &mut line)?;
+            if read == 0{
+                return Ok(());
+            }
+            f(offset, &line);
+            offset += read;
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
@@ -0,0 +1,66 @@
+use regex::bytes::RegexBuilder;
+use crate::grep::{multiline::Multiline, replace::Match};
+
+fn multiline(pattern: &str, contents: &str) -> Multiline{
+    let reg = RegexBuilder::new(pattern).multi_line(true).build().unwrap();
+    Multiline::read(&reg, contents.as_bytes(), None).unwrap()
+}
+
+// The positions of the parts of the matches in a line
+fn positions(matches: Vec<Match>) -> Vec<(usize, usize)>{
+    matches.into_iter().map(|matched| (matched.start, matched.end)).collect()
+}
+
+#[test]
+fn matches_in_test(){
+    // The lines are "fn main(" at 0, "    x," at 9, ") {" at 16 and "}" at 20
+    let contents = "fn main(\n    x,\n) {\n}";
+    let multiline = multiline(r"main\(\n\s*x", contents);
+    assert_eq!(vec![(3, 8)], positions(multiline.matches_in(0, b"fn main(")));
+    assert_eq!(vec![(0, 5)], positions(multiline.matches_in(9, b"    x,")));
+    assert!(multiline.matches_in(16, b") {").is_empty());
+    assert!(multiline.matches_in(20, b"}").is_empty());
+
+    // A match ending at a line terminator covers it, but not the next line
+    let multiline = self::multiline(r"x,\n", contents);
+    assert_eq!(vec![(4, 6)], positions(multiline.matches_in(9, b"    x,")));
+    assert!(multiline.matches_in(16, b") {").is_empty());
+
+    // Several matches in a line, and empty matches
+    let multiline = self::multiline("a", "a a\nb");
+    assert_eq!(vec![(0, 1), (2, 3)], positions(multiline.matches_in(0, b"a a")));
+    let multiline = self::multiline("^", "a\nb");
+    assert_eq!(vec![(0, 0)], positions(multiline.matches_in(0, b"a")));
+    assert_eq!(vec![(0, 0)], positions(multiline.matches_in(2, b"b")));
+
+    // The replacement is written where the match starts
+    let reg = RegexBuilder::new(r"\(\n\s*(\w)").build().unwrap();
+    let multiline = Multiline::read(&reg, "f(\n x)".as_bytes(), Some(b"[$1]")).unwrap();
+    assert_eq!(vec![Match{ start: 1, end: 2, replacement: Some(b"[x]".to_vec()) }], multiline.matches_in(0, b"f("));
+    assert_eq!(vec![Match{ start: 0, end: 2, replacement: Some(Vec::new()) }], multiline.matches_in(3, b" x)"));
+}
+
+#[test]
+fn counters_test(){
+    let contents = "fn main(\n    x,\n) {\n}\nfn main(\n    x,\n)";
+    let multiline = multiline(r"main\(\n\s*x", contents);
+    assert_eq!(4, multiline.count(false, None).unwrap());
+    assert_eq!(2, multiline.total_count(None).unwrap());
+    // With -m, a match is counted if it starts in one of the first lines covered
+    assert_eq!(3, multiline.count(false, Some(3)).unwrap());
+    // With -v, the lines that aren't covered are counted
+    assert_eq!(3, multiline.count(true, None).unwrap());
+    assert_eq!(1, multiline.total_count(Some(2)).unwrap());
+    assert_eq!(2, multiline.total_count(Some(3)).unwrap());
+    assert_eq!(0, multiline.total_count(Some(0)).unwrap());
+    assert!(multiline.files_with_matches(false));
+    assert!(!multiline.files_with_matches(true));
+
+    let multiline = self::multiline(r"(?s).*", contents);
+    assert_eq!(7, multiline.count(false, None).unwrap());
+    assert_eq!(0, multiline.count(true, None).unwrap());
+    assert!(!multiline.files_with_matches(true));
+    let multiline = self::multiline("grep", contents);
+    assert!(!multiline.files_with_matches(false));
+    assert!(multiline.files_with_matches(true));
+}
\ No newline at end of file
@@ -1,20 +1,26 @@
 use clap::Args;
 
-#[derive(Args)]
-#[group(multiple=true)] // These arguments are grouped because they are not compatible with the arguments in Counters, so we use this ArgGroup to handle this possibility
+#[derive(Args, Clone, Copy)]
+#[group(multiple=true)]
 pub struct Options{
     /// Set this flag on to find which files matche the pattern. 
-    /// Used in combination with -v will return the files whose contents does NOT much the pattern. 
+    /// Used in combination with -v will return the files whose contents does NOT much the pattern, with or without -U. 
     /// The behavior is not affected if used together with -n or -o, an error is not thrown anyway.
-    /// Not allowed to use it together with -c or --total-count.
+    /// Used together with -c or --total-count, the files with a count of 0 are skipped and the counts are prefixed with the name of their file even if there's a single one.
     #[arg(short='l', long)]
     pub files_with_matches: bool,
 
+    /// Set this flag on to find which files don't match the pattern, the opposite of -l.
+    /// Used in combination with -v will return the files whose contents match the pattern, with or without -U.
+    /// Not allowed to use it together with -l, -c or --total-count.
+    #[arg(short='L', long, conflicts_with_all = ["files_with_matches", "count", "total_count"])]
+    pub files_without_match: bool,
+
     /// Set this flag on to enumerate the lines where the occurrence happened. Not allowed to use it together with -c or --total-count.
     #[arg(short='n', long)]
     pub line_number: bool,
 
-    /// Set this flag on to find the lines where the pattern is not satisfied. Not allowed to use it together with -c or --total-count.
+    /// Set this flag on to find the lines where the pattern is not satisfied. Used together with -c, the lines not matching the pattern are counted. Not allowed to use it together with --total-count.
     #[arg(short = 'v', long)]
     pub invert_match: bool,
 
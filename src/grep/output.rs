@@ -0,0 +1,254 @@
+use clap::{Args, ArgAction};
+use std::{mem, sync::atomic::{AtomicUsize, Ordering}, time::Duration};
+use crate::{base, grep::{color::Palette, replace::Replace, limits::Budget}};
+
+#[derive(Args, Clone)]
+pub struct Output{
+    /// Set this flag on to prefix every line with the name of its file, as in file:line. By default, the names are only shown if several files are searched or -R is set.
+    #[arg(short = 'H', long, overrides_with = "no_filename")]
+    pub with_filename: bool,
+
+    /// Set this flag on to never prefix the lines with the name of their file.
+    #[arg(short = 'h', long, overrides_with = "with_filename")]
+    pub no_filename: bool,
+
+    /// Specify the line printed between the groups of lines that aren't next to each other when context is shown with -A, -B or -C.
+    #[arg(long, value_name = "SEP", default_value = "--")]
+    pub group_separator: String,
+
+    /// Set this flag on to print nothing between the groups of context lines.
+    #[arg(long)]
+    pub no_group_separator: bool,
+
+    /// Set this flag on to prefix every line with the offset of its first byte in its file (from 0), after its line number. With -o, the offset of every match is shown instead.
+    #[arg(short = 'b', long)]
+    pub byte_offset: bool,
+
+    /// Set this flag on to prefix every selected line with the column of its first match (from 1, counting bytes), after its line number. The selected lines without a match (eg, with -v) are written at the column 1. It implies -n.
+    #[arg(long)]
+    pub column: bool,
+
+    /// Set this flag on to print every match on its own line as file:line:column:text, as in the quickfix lists of vim, so the results can be opened straight from an editor. It implies -H, -n and --column. Not allowed to use it together with -l, -L, -c, --total-count, -v, --json or the context lines.
+    #[arg(long, conflicts_with_all = ["files_with_matches", "files_without_match", "count", "total_count", "invert_match", "json", "in_place", "after_context", "before_context", "context"])]
+    pub vimgrep: bool,
+
+    /// Set this flag on to print the results as JSON Lines, similar to the JSON output of ripgrep: a begin object when a file matches, a match or context object for every line (with its line number, its byte offset in the file and the offsets of every match in the line), an end object with the statistics of the file and a summary object at the end. The lines are written without their line terminator. A binary file matching the pattern is reported with a binary object. Not allowed to use it together with -l, -L, -c or --total-count.
+    #[arg(long, conflicts_with_all = ["files_with_matches", "files_without_match", "count", "total_count"])]
+    pub json: bool,
+
+    /// Print help. Note that -h is taken by --no-filename, as in GNU grep.
+    #[arg(long, action = ArgAction::Help)]
+    pub help: Option<bool>
+}
+
+// How the results are written, as in GNU grep: file:line:text for the selected lines, file-line-text for the context lines and a separator between the groups of lines that aren't next to each other. With json, every line is written as a JSON object instead
+pub struct Format{
+    pub with_filename: bool,
+    pub group_separator: Option<String>, // Only if context lines are printed
+    pub palette: Palette,
+    pub json: bool,
+    pub totals: Totals,
+    pub replace: Option<Replace>, // Only if the matches are replaced
+    pub max_count: Option<usize>, // The lines selected in every file, at most
+    pub budget: Budget,
+    pub byte_offset: bool,
+    pub column: bool,
+    pub vimgrep: bool // Every match is written on its own line
+}
+
+// Where the lines of a file go as soon as they're found, together with the name of the file and the format they're written in. The lines are gathered until they take batch bytes (0 gives every line on its own) and then given to emit, which returns false once they aren't wanted anymore (eg, the search has been stopped), so the rest of the file doesn't need to be read
+pub struct FileOutput<'a>{
+    pub name: &'a str,
+    pub format: &'a Format,
+    batch: usize,
+    pending: String,
+    emit: &'a mut dyn FnMut(String) -> bool,
+    open: bool
+}
+
+// Where a line is in its file, as written before its text: its line number (from 0), the column of its first match (from 0) and the offset of its first byte. Those that aren't written are None
+#[derive(Default, Clone, Copy)]
+pub struct Position{
+    pub line_number: Option<usize>,
+    pub column: Option<usize>,
+    pub byte_offset: Option<usize>
+}
+
+// The statistics of the search in a file, written at the end of its JSON output
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct Stats{
+    pub matched_lines: usize,
+    pub matches: usize,
+    pub bytes_searched: usize
+}
+
+// The statistics of the whole search, for the summary of the JSON output. The workers add the statistics of every file as they finish it
+#[derive(Default)]
+pub struct Totals{
+    searches: AtomicUsize,
+    searches_with_match: AtomicUsize,
+    matched_lines: AtomicUsize,
+    matches: AtomicUsize,
+    bytes_searched: AtomicUsize
+}
+
+impl Format{
+    // Nothing but the text of the lines is written
+    #[cfg(test)]
+    pub fn plain() -> Format{
+        Format{ with_filename: false, group_separator: None, palette: Palette::plain(), json: false, totals: Totals::default(), replace: None, max_count: None, budget: Budget::default(), byte_offset: false, column: false, vimgrep: false }
+    }
+
+    // The name of a file, as it's written on its own (eg, with -l)
+    pub fn file_name(&self, file: &str) -> String{
+        Palette::paint(&self.palette.file_name, file)
+    }
+
+    // The prefix of a line: its file name, line number, column and byte offset, if they're requested, each of them followed by a separator telling selected lines (:) and context lines (-) apart
+    pub fn prefix(&self, file: &str, position: Position, is_context_line: bool) -> String{
+        let separator = Palette::paint(&self.palette.separator, if is_context_line{ "-" } else{ ":" });
+        let mut prefix = String::new();
+        if self.with_filename{
+            prefix.push_str(&self.file_name(file));
+            prefix.push_str(&separator);
+        }
+        let numbers = [
+            (position.line_number.map(|line_number| line_number + 1), &self.palette.line_number),
+            (position.column.map(|column| column + 1), &self.palette.line_number),
+            (position.byte_offset, &self.palette.byte_offset)
+        ];
+        for (number, color) in numbers{
+            if let Some(number) = number{
+                prefix.push_str(&Palette::paint(color, &number.to_string()));
+                prefix.push_str(&separator);
+            }
+        }
+        prefix
+    }
+
+    // A line of a file with its prefix, highlighting the given matches (start and end of each of them in the line) with the colors of the palette. Non UTF-8 sequences in the line are replaced by the replacement character
+    pub fn line(&self, file: &str, position: Position, line: &[u8], matches: &[(usize, usize)], is_context_line: bool) -> String{
+        let (line_color, match_color) = if is_context_line{
+            (&self.palette.context_line, &self.palette.context_match)
+        }
+        else{
+            (&self.palette.selected_line, &self.palette.selected_match)
+        };
+        let mut text = self.prefix(file, position, is_context_line);
+        let mut position = 0;
+        for &(start, end) in matches.iter().filter(|(start, end)| start < end){ // Empty matches don't have anything to highlight
+            text.push_str(&Palette::paint(line_color, &String::from_utf8_lossy(&line[position..start])));
+            text.push_str(&Palette::paint(match_color, &String::from_utf8_lossy(&line[start..end])));
+            position = end;
+        }
+        text.push_str(&Palette::paint(line_color, &String::from_utf8_lossy(&line[position..])));
+        text
+    }
+
+    // The template replacing the matches, if any
+    pub fn template(&self) -> Option<&[u8]>{
+        self.replace.as_ref().and_then(Replace::template)
+    }
+
+    // The line written between two groups of lines, if any
+    pub fn separator(&self) -> Option<String>{
+        self.group_separator.as_ref().map(|separator| Palette::paint(&self.palette.separator, separator))
+    }
+
+    // A line of a file as a JSON object, with its line number (from 1), the offset of its first byte in the file and the matches found in it
+    pub fn json_line(&self, file: &str, line_number: usize, offset: usize, line: &[u8], matches: &[(usize, usize)], is_context_line: bool) -> String{
+        let submatches = matches
+            .iter()
+            .map(|&(start, end)| format!("{{\"match\":{},\"start\":{},\"end\":{}}}", base::json_text(&line[start..end]), start, end))
+            .collect::<Vec<String>>()
+            .join(",");
+        format!(
+            "{{\"type\":\"{}\",\"data\":{{\"path\":{},\"lines\":{},\"line_number\":{},\"absolute_offset\":{},\"submatches\":[{}]}}}}",
+            if is_context_line{ "context" } else{ "match" },
+            base::json_text(file.as_bytes()),
+            base::json_text(line),
+            line_number + 1,
+            offset,
+            submatches
+        )
+    }
+
+    // The object written before the lines of a file
+    pub fn json_begin(&self, file: &str) -> String{
+        format!("{{\"type\":\"begin\",\"data\":{{\"path\":{}}}}}", base::json_text(file.as_bytes()))
+    }
+
+    // The object written after the lines of a file
+    pub fn json_end(&self, file: &str, stats: Stats) -> String{
+        format!(
+            "{{\"type\":\"end\",\"data\":{{\"path\":{},\"stats\":{{\"matched_lines\":{},\"matches\":{},\"bytes_searched\":{}}}}}}}",
+            base::json_text(file.as_bytes()),
+            stats.matched_lines,
+            stats.matches,
+            stats.bytes_searched
+        )
+    }
+
+    // The object written instead of the lines of a binary file matching the pattern
+    pub fn json_binary(&self, file: &str) -> String{
+        format!("{{\"type\":\"binary\",\"data\":{{\"path\":{}}}}}", base::json_text(file.as_bytes()))
+    }
+
+    // The object written at the end of the search, with the statistics of every file searched
+    pub fn json_summary(&self, elapsed: Duration) -> String{
+        let totals = &self.totals;
+        format!(
+            "{{\"type\":\"summary\",\"data\":{{\"elapsed_total\":{{\"secs\":{},\"nanos\":{},\"human\":\"{:.6}s\"}},\"stats\":{{\"searches\":{},\"searches_with_match\":{},\"matched_lines\":{},\"matches\":{},\"bytes_searched\":{}}}}}}}",
+            elapsed.as_secs(),
+            elapsed.subsec_nanos(),
+            elapsed.as_secs_f64(),
+            totals.searches.load(Ordering::Relaxed),
+            totals.searches_with_match.load(Ordering::Relaxed),
+            totals.matched_lines.load(Ordering::Relaxed),
+            totals.matches.load(Ordering::Relaxed),
+            totals.bytes_searched.load(Ordering::Relaxed)
+        )
+    }
+}
+
+impl<'a> FileOutput<'a>{
+    pub fn new(name: &'a str, format: &'a Format, batch: usize, emit: &'a mut dyn FnMut(String) -> bool) -> FileOutput<'a>{
+        FileOutput{ name, format, batch, pending: String::new(), emit, open: true }
+    }
+
+    // Adds a line to the ones to be given to emit, unless they aren't wanted anymore
+    pub fn write(&mut self, line: String){
+        if self.open{
+            self.pending.push_str(&line);
+            self.pending.push('\n');
+            if self.pending.len() >= self.batch{
+                self.flush();
+            }
+        }
+    }
+
+    // Gives the lines gathered so far to emit
+    pub fn flush(&mut self){
+        if self.open && !self.pending.is_empty(){
+            self.open = (self.emit)(mem::take(&mut self.pending));
+        }
+    }
+
+    pub fn is_open(&self) -> bool{
+        self.open
+    }
+}
+
+impl Totals{
+    // Adds the statistics of a file that has been searched
+    pub fn add(&self, stats: Stats, matched: bool){
+        self.searches.fetch_add(1, Ordering::Relaxed);
+        self.searches_with_match.fetch_add(usize::from(matched), Ordering::Relaxed);
+        self.matched_lines.fetch_add(stats.matched_lines, Ordering::Relaxed);
+        self.matches.fetch_add(stats.matches, Ordering::Relaxed);
+        self.bytes_searched.fetch_add(stats.bytes_searched, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
@@ -0,0 +1,71 @@
+use std::time::Duration;
+use crate::grep::{color::{Color, ColorWhen}, output::{Format, Stats, Position}};
+
+#[test]
+fn line_test(){
+    let plain = Format::plain();
+    assert_eq!("Hey", plain.line("file", Position::default(), b"Hey", &[], false));
+    assert_eq!("Hey", plain.line("file", Position::default(), b"Hey", &[], true));
+    assert_eq!("1:Hey", plain.line("file", Position{ line_number: Some(0), ..Position::default() }, b"Hey", &[], false));
+    assert_eq!("1-Hey", plain.line("file", Position{ line_number: Some(0), ..Position::default() }, b"Hey", &[], true));
+    assert_eq!("1:Hey \u{FFFD}", plain.line("file", Position{ line_number: Some(0), ..Position::default() }, b"Hey \xff", &[], false));
+
+    // With the name of the file, as in GNU grep
+    let with_filename = Format{ with_filename: true, ..Format::plain() };
+    assert_eq!("file:3:Hey", with_filename.line("file", Position{ line_number: Some(2), ..Position::default() }, b"Hey", &[], false));
+    assert_eq!("file-3-Hey", with_filename.line("file", Position{ line_number: Some(2), ..Position::default() }, b"Hey", &[], true));
+    assert_eq!("file:Hey", with_filename.line("file", Position::default(), b"Hey", &[], false));
+
+    // Colored output: the file name, the matches, the line number and the separators are highlighted
+    let colored = Format{ with_filename: true, group_separator: Some("--".to_string()), palette: Color{ color: ColorWhen::Always }.palette(false, Some("ms=31:mc=33:fn=35:ln=32:se=36:cx=2")), ..Format::plain() };
+    assert_eq!(
+        "\x1b[35m\x1b[Kfile\x1b[m\x1b[K\x1b[36m\x1b[K:\x1b[m\x1b[K\x1b[32m\x1b[K1\x1b[m\x1b[K\x1b[36m\x1b[K:\x1b[m\x1b[KHey \x1b[31m\x1b[Kyou\x1b[m\x1b[K!",
+        colored.line("file", Position{ line_number: Some(0), ..Position::default() }, b"Hey you!", &[(4, 7)], false)
+    );
+    let colored = Format{ with_filename: false, ..colored };
+    assert_eq!(
+        "\x1b[2m\x1b[KHey \x1b[m\x1b[K\x1b[33m\x1b[Kyou\x1b[m\x1b[K",
+        colored.line("file", Position::default(), b"Hey you", &[(4, 7), (7, 7)], true)
+    );
+    assert_eq!(Some("\x1b[36m\x1b[K--\x1b[m\x1b[K".to_string()), colored.separator());
+}
+
+#[test]
+fn prefix_test(){
+    let with_filename = Format{ with_filename: true, group_separator: Some("==".to_string()), ..Format::plain() };
+    assert_eq!("file:", with_filename.prefix("file", Position::default(), false));
+    assert_eq!("file-10-", with_filename.prefix("file", Position{ line_number: Some(9), ..Position::default() }, true));
+    assert_eq!("", Format::plain().prefix("file", Position::default(), false));
+    // The column and the byte offset follow the line number
+    assert_eq!("file:3:5:120:", with_filename.prefix("file", Position{ line_number: Some(2), column: Some(4), byte_offset: Some(120) }, false));
+    assert_eq!("0-", Format::plain().prefix("file", Position{ byte_offset: Some(0), ..Position::default() }, true));
+    assert_eq!(Some("==".to_string()), with_filename.separator());
+    assert_eq!(None, Format::plain().separator());
+}
+
+#[test]
+fn json_test(){
+    let json = Format{ json: true, ..Format::plain() };
+    assert_eq!(
+        r#"{"type":"match","data":{"path":{"text":"file"},"lines":{"text":"Hey you"},"line_number":3,"absolute_offset":12,"submatches":[{"match":{"text":"you"},"start":4,"end":7}]}}"#,
+        json.json_line("file", 2, 12, b"Hey you", &[(4, 7)], false)
+    );
+    assert_eq!(
+        r#"{"type":"context","data":{"path":{"text":"file"},"lines":{"text":"\"Hey\"\t"},"line_number":1,"absolute_offset":0,"submatches":[]}}"#,
+        json.json_line("file", 0, 0, b"\"Hey\"\t", &[], true)
+    );
+    assert_eq!(r#"{"type":"begin","data":{"path":{"text":"file"}}}"#, json.json_begin("file"));
+    assert_eq!(
+        r#"{"type":"end","data":{"path":{"text":"file"},"stats":{"matched_lines":2,"matches":3,"bytes_searched":40}}}"#,
+        json.json_end("file", Stats{ matched_lines: 2, matches: 3, bytes_searched: 40 })
+    );
+    assert_eq!(r#"{"type":"binary","data":{"path":{"text":"file"}}}"#, json.json_binary("file"));
+
+    // The summary adds the statistics of every file searched
+    json.totals.add(Stats{ matched_lines: 2, matches: 3, bytes_searched: 40 }, true);
+    json.totals.add(Stats{ matched_lines: 0, matches: 0, bytes_searched: 10 }, false);
+    assert_eq!(
+        r#"{"type":"summary","data":{"elapsed_total":{"secs":1,"nanos":500000000,"human":"1.500000s"},"stats":{"searches":2,"searches_with_match":1,"matched_lines":2,"matches":3,"bytes_searched":50}}}"#,
+        json.json_summary(Duration::from_millis(1500))
+    );
+}
\ No newline at end of file
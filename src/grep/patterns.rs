@@ -0,0 +1,188 @@
+use clap::Args;
+use regex::bytes::Regex;
+use std::fs;
+use crate::{clu_errors::CluErrors, case::{self, Case}};
+
+#[derive(Args, Clone)]
+pub struct Patterns{
+    /// Specify a pattern to look for. It can be repeated to look for several patterns at once, and a line is selected if it matches any of them. If it's given, every positional argument is a path.
+    #[arg(short = 'e', long, value_name = "PATTERN")]
+    pub regexp: Vec<String>,
+
+    /// Specify a file containing the patterns to look for, one per line. It can be repeated and combined with -e. An empty file doesn't match anything. If it's given, every positional argument is a path.
+    #[arg(short = 'f', long, value_name = "FILE")]
+    pub file: Vec<String>,
+
+    /// Set this flag on to take the patterns as fixed strings instead of regular expressions, so nothing has to be escaped. Every character of the patterns stands for itself, eg: -F 'a.b' only matches a.b.
+    #[arg(short = 'F', long, conflicts_with_all = ["extended_regexp", "basic_regexp"])]
+    pub fixed_strings: bool,
+
+    /// Set this flag on to take the patterns as extended regular expressions, in the syntax of the Rust regex crate. This is the default.
+    #[arg(short = 'E', long, conflicts_with = "basic_regexp")]
+    pub extended_regexp: bool,
+
+    /// Set this flag on to take the patterns as POSIX basic regular expressions, as GNU grep does by default: \( \) \{ \} \| \+ and \? are the operators, while ( ) { } | + and ? are plain characters.
+    #[arg(short = 'G', long)]
+    pub basic_regexp: bool,
+
+    /// Set this flag on to select only the matches that are whole words, so they're neither preceded nor followed by letters, digits or underscores.
+    #[arg(short = 'w', long)]
+    pub word_regexp: bool,
+
+    /// Set this flag on to select only the matches that are whole lines. It takes preference over -w.
+    #[arg(short = 'x', long)]
+    pub line_regexp: bool,
+
+    /// Set this flag on to let the patterns match across lines, eg: 'fn main\(\)\n\{'. The whole file is read at once, and every line covered by a match is selected. ^ and $ match at the start and the end of every line.
+    #[arg(short = 'U', long)]
+    pub multiline: bool,
+
+    /// Set this flag on to let . match the line terminators as well in multiline mode.
+    #[arg(long, requires = "multiline")]
+    pub multiline_dotall: bool
+}
+
+impl Patterns{
+    // If there are patterns given with -e or -f, the positional pattern is actually a path
+    pub fn is_empty(&self) -> bool{
+        self.regexp.is_empty() && self.file.is_empty()
+    }
+
+    // Collects every pattern: the positional one, the ones given with -e and the ones read from the files of -f
+    pub fn collect(&self, pattern: Option<&str>) -> Result<Vec<String>, CluErrors>{
+        let mut patterns: Vec<String> = pattern.into_iter().map(str::to_string).chain(self.regexp.iter().cloned()).collect();
+        for file in &self.file{
+            let contents = fs::read_to_string(file).map_err(|_error| CluErrors::InputError(format!("{} cannot be read", file)))?;
+            patterns.extend(contents.lines().map(str::to_string));
+        }
+        Ok(patterns)
+    }
+
+    // Compiles every pattern into a single regex, so the lines are scanned just once no matter how many patterns there are. The regex engine looks for alternations of literals, as the known signatures of some errors or the fixed strings, with Aho-Corasick or memchr without running any automaton. Without patterns, the regex never matches
+    pub fn regex(&self, patterns: &[String], case: &Case) -> Result<Regex, CluErrors>{
+        let insensitive = case.is_insensitive(patterns);
+        let build = |pattern: &str| case::builder(pattern, insensitive, case.case_folding)
+            .multi_line(self.multiline)
+            .crlf(self.multiline)
+            .dot_matches_new_line(self.multiline_dotall)
+            .build();
+        let translated: Vec<String> = patterns.iter().map(|pattern| self.translate(pattern)).collect();
+        let alternation = if translated.is_empty(){
+            r"[^\s\S]".to_string()
+        }
+        else{
+            translated.iter().map(|pattern| format!("(?:{})", pattern)).collect::<Vec<String>>().join("|")
+        };
+        let alternation = if self.line_regexp{
+            format!("^(?:{})$", alternation)
+        }
+        else if self.word_regexp{ // Unlike \b, the half boundaries also let the patterns start or end with a character that isn't part of a word, as in GNU grep
+            format!(r"\b{{start-half}}(?:{})\b{{end-half}}", alternation)
+        }
+        else{
+            alternation
+        };
+        // If the regex cannot be built, the patterns are checked one by one to report the wrong one
+        build(&alternation).map_err(|_error|
+            CluErrors::RegexError(
+                patterns
+                    .iter()
+                    .zip(&translated)
+                    .find(|(_pattern, translated)| build(translated).is_err())
+                    .map_or(alternation.clone(), |(pattern, _translated)| pattern.to_string())
+            )
+        )
+    }
+
+    // Translates a pattern into the syntax of the regex crate
+    fn translate(&self, pattern: &str) -> String{
+        if self.fixed_strings{
+            regex::escape(pattern)
+        }
+        else if self.basic_regexp{
+            Self::basic_to_extended(pattern)
+        }
+        else{
+            pattern.to_string()
+        }
+    }
+
+    // Translates a POSIX basic regular expression, as GNU grep reads it, into the syntax of the regex crate. The escaped \( \) \{ \} \| \+ and \? are the operators, while the plain ones are literals. * is a literal at the start of an expression, ^ is only an anchor at its start and $ at its end. As GNU extensions, \< and \> are the boundaries of a word and \w \W \s \S \b \B keep their meaning
+    fn basic_to_extended(pattern: &str) -> String{
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut translated = String::new();
+        let mut at_start = true; // At the start of an expression, after \( or \|
+        let mut position = 0;
+        while position < chars.len(){
+            let character = chars[position];
+            let starts = at_start;
+            at_start = false;
+            match character{
+                '\\' if position + 1 < chars.len() => {
+                    position += 1;
+                    match chars[position]{
+                        '(' | '|' => {
+                            translated.push(chars[position]);
+                            at_start = true;
+                        },
+                        ')' | '{' | '}' | '+' | '?' => translated.push(chars[position]),
+                        '<' => translated.push_str(r"\b{start}"),
+                        '>' => translated.push_str(r"\b{end}"),
+                        escaped @ ('w' | 'W' | 's' | 'S' | 'b' | 'B' | '1'..='9') => { // The back-references aren't supported by the regex crate, so they're left to be reported
+                            translated.push('\\');
+                            translated.push(escaped);
+                        },
+                        escaped => translated.push_str(&regex::escape(&escaped.to_string()))
+                    }
+                },
+                '\\' => translated.push('\\'), // A trailing backslash is wrong, and it's left to be reported
+                '^' if starts => {
+                    translated.push('^');
+                    at_start = true;
+                },
+                '$' if chars[position + 1..].is_empty() || chars[position + 1..].starts_with(&['\\', ')']) || chars[position + 1..].starts_with(&['\\', '|']) => translated.push('$'),
+                '*' if starts => translated.push_str(r"\*"),
+                '.' | '*' => translated.push(character),
+                '[' => position = Self::copy_bracket(&chars, position, &mut translated),
+                character => translated.push_str(&regex::escape(&character.to_string()))
+            }
+            position += 1;
+        }
+        translated
+    }
+
+    // Copies the bracket expression starting at position, escaping the characters that the regex crate reads as operators inside classes. A ] right after [ or [^ is part of the class, and [:name:] is a class of characters. Returns the position of the closing ], or the end of the pattern if it's unterminated, which is left to be reported
+    fn copy_bracket(chars: &[char], mut position: usize, translated: &mut String) -> usize{
+        translated.push('[');
+        position += 1;
+        if chars.get(position) == Some(&'^'){
+            translated.push('^');
+            position += 1;
+        }
+        if chars.get(position) == Some(&']'){
+            translated.push_str(r"\]");
+            position += 1;
+        }
+        while position < chars.len() && chars[position] != ']'{
+            if chars[position..].starts_with(&['[', ':']){
+                if let Some(end) = chars[position + 2..].windows(2).position(|window| window == [':', ']']){
+                    translated.extend(&chars[position..position + end + 4]);
+                    position += end + 4;
+                    continue;
+                }
+            }
+            if matches!(chars[position], '\\' | '[' | '&' | '~'){
+                translated.push('\\');
+            }
+            translated.push(chars[position]);
+            position += 1;
+        }
+        if position < chars.len(){
+            translated.push(']');
+        }
+        position
+    }
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
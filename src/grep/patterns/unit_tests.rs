@@ -0,0 +1,124 @@
+use std::{env, fs};
+use crate::{clu_errors::CluErrors, case::{Case, Folding}, grep::patterns::Patterns};
+
+// The patterns given without any flag
+fn flags() -> Patterns{
+    Patterns{ regexp: vec![], file: vec![], fixed_strings: false, extended_regexp: false, basic_regexp: false, word_regexp: false, line_regexp: false, multiline: false, multiline_dotall: false }
+}
+
+// The case options, ignoring the case or not
+fn case(ignore_case: bool) -> Case{
+    Case{ ignore_case, smart_case: false, no_ignore_case: false, case_folding: Folding::Unicode }
+}
+
+#[test]
+fn collect_test(){
+    let file = env::temp_dir().join("amazing_clu_patterns_collect_test.txt");
+    fs::write(&file, "ERROR\r\nfailed to [a-z]+\n").unwrap();
+    let file = file.to_str().unwrap().to_string();
+
+    // The positional pattern goes first, then the ones given with -e and the ones read from the files, without their line terminators
+    let patterns = flags();
+    assert!(patterns.is_empty());
+    assert_eq!(vec!["grep".to_string()], patterns.collect(Some("grep")).unwrap());
+    let patterns = Patterns{ regexp: vec!["one".to_string(), "two".to_string()], file: vec![file.clone()], ..flags() };
+    assert!(!patterns.is_empty());
+    assert_eq!(vec!["one", "two", "ERROR", "failed to [a-z]+"], patterns.collect(None).unwrap());
+
+    // A file that cannot be read
+    let patterns = Patterns{ regexp: vec![], file: vec!["missing_patterns.txt".to_string()], ..flags() };
+    assert_eq!(CluErrors::InputError("missing_patterns.txt cannot be read".to_string()), patterns.collect(None).unwrap_err());
+    fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn regex_test(){
+    // A line matches if any of the patterns matches it
+    let list = vec!["ERROR".to_string(), "fail(ed)?".to_string(), "a|b".to_string()];
+    let reg = flags().regex(&list, &case(false)).unwrap();
+    assert!(reg.is_match(b"An ERROR"));
+    assert!(reg.is_match(b"It failed"));
+    assert!(reg.is_match(b"b"));
+    assert!(!reg.is_match(b"error"));
+    assert!(flags().regex(&list, &case(true)).unwrap().is_match(b"error"));
+
+    // Without patterns nothing matches, while an empty pattern matches everything
+    assert!(!flags().regex(&[], &case(false)).unwrap().is_match(b"Hey you"));
+    assert!(!flags().regex(&[], &case(false)).unwrap().is_match(b""));
+    assert!(flags().regex(&["".to_string()], &case(false)).unwrap().is_match(b"Hey you"));
+
+    // The wrong pattern is reported
+    let list = vec!["ERROR".to_string(), "[a-z+".to_string()];
+    assert_eq!(CluErrors::RegexError("[a-z+".to_string()), flags().regex(&list, &case(false)).unwrap_err());
+}
+
+#[test]
+fn fixed_strings_test(){
+    let fixed = Patterns{ fixed_strings: true, ..flags() };
+    let reg = fixed.regex(&["a.b()".to_string(), "[x]".to_string()], &case(false)).unwrap();
+    assert!(reg.is_match(b"call a.b() now"));
+    assert!(reg.is_match(b"[x]"));
+    assert!(!reg.is_match(b"axb()"));
+    assert!(!reg.is_match(b"x"));
+    // What would be a wrong regex is fine as a fixed string
+    assert!(fixed.regex(&["[a-z+".to_string()], &case(false)).unwrap().is_match(b"[a-z+"));
+}
+
+#[test]
+fn word_and_line_regexp_test(){
+    let words = Patterns{ word_regexp: true, ..flags() };
+    let reg = words.regex(&["grep".to_string(), "-v".to_string()], &case(false)).unwrap();
+    assert!(reg.is_match(b"I'm grep"));
+    assert!(reg.is_match(b"grep, the tool"));
+    assert!(!reg.is_match(b"I'm agrep"));
+    assert!(!reg.is_match(b"grep_tool"));
+    assert!(reg.is_match(b"agrep grep")); // The second occurrence is a whole word
+    assert!(reg.is_match(b"grep -v")); // The patterns may start with something that isn't part of a word
+    assert!(reg.is_match(b"grep-value")); // The dash isn't part of a word
+    assert!(!reg.is_match(b"a-value"));
+
+    let lines = Patterns{ line_regexp: true, ..flags() };
+    let reg = lines.regex(&["grep".to_string(), "I'm.*".to_string()], &case(false)).unwrap();
+    assert!(reg.is_match(b"grep"));
+    assert!(reg.is_match(b"I'm grep"));
+    assert!(!reg.is_match(b"a grep"));
+    // -x takes preference over -w
+    let both = Patterns{ line_regexp: true, word_regexp: true, ..flags() };
+    assert!(!both.regex(&["grep".to_string()], &case(false)).unwrap().is_match(b"a grep"));
+}
+
+#[test]
+fn basic_regexp_test(){
+    let translations = [
+        ("a.b*", "a.b*"),
+        (r"\(ab\)\{2\}", "(ab){2}"),
+        ("(ab){2}", r"\(ab\)\{2\}"),
+        (r"a\|b", "a|b"),
+        ("a|b+c?", r"a\|b\+c\?"),
+        (r"a\+b\?", "a+b?"),
+        ("*a", r"\*a"),
+        (r"^*a", r"^\*a"),
+        (r"\(*a\)", r"(\*a)"),
+        ("a^b$c", r"a\^b\$c"),
+        (r"^a$", "^a$"),
+        (r"\(a$\)", "(a$)"),
+        (r"\<grep\>", r"\b{start}grep\b{end}"),
+        (r"\w\s\.", r"\w\s\."),
+        ("[]a-z]", r"[\]a-z]"),
+        ("[^]a]", r"[^\]a]"),
+        ("[[:digit:]x]", "[[:digit:]x]"),
+        (r"[\[&]", r"[\\\[\&]")
+    ];
+    for (basic, extended) in translations{
+        assert_eq!(extended, Patterns::basic_to_extended(basic), "{}", basic);
+    }
+
+    let basic = Patterns{ basic_regexp: true, ..flags() };
+    let reg = basic.regex(&[r"\(ab\)\{2\}(c)".to_string()], &case(false)).unwrap();
+    assert!(reg.is_match(b"abab(c)"));
+    assert!(!reg.is_match(b"ababc"));
+    // The wrong patterns are reported as they were written
+    assert_eq!(CluErrors::RegexError(r"\(a".to_string()), basic.regex(&[r"\(a".to_string()], &case(false)).unwrap_err());
+    assert_eq!(CluErrors::RegexError("[a".to_string()), basic.regex(&["[a".to_string()], &case(false)).unwrap_err());
+    assert!(basic.regex(&[r"a\".to_string()], &case(false)).is_err());
+}
\ No newline at end of file
@@ -0,0 +1,169 @@
+use clap::Args;
+use regex::bytes::Regex;
+use std::{fs, iter, path::Path, process};
+use crate::{clu_errors::CluErrors, grep::Grep};
+
+#[derive(Args, Clone)]
+pub struct Replace{
+    /// Specify a template to replace every match with in the output. $1, $2... are replaced by the groups captured by the pattern, ${name} by the group named name (eg, with (?P<name>[0-9]+)) and $$ by a single $. Note that $name takes the longest name possible, so write ${1}a instead of $1a. The files aren't modified unless --in-place is given.
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "json")]
+    pub replace: Option<String>,
+
+    /// Set this flag on to write the replacements into the files instead of printing their lines, and print the names of the files changed. Every file is written atomically, through a temporary file renamed over it, after keeping a copy of it (see --backup-suffix). Binary files are only edited together with -a, and the standard input cannot be edited.
+    #[arg(long, requires = "replace", conflicts_with_all = ["files_with_matches", "files_without_match", "invert_match", "only_matching", "count", "total_count", "max_count", "max_total", "quiet"])]
+    pub in_place: bool,
+
+    /// Specify the suffix added to the name of a file edited with --in-place to name its backup. An empty suffix (--backup-suffix '') skips the backups.
+    #[arg(long, value_name = "SUFFIX", default_value = ".bak", requires = "in_place")]
+    pub backup_suffix: String,
+
+    /// Set this flag on to print the changes --in-place would make, as an unified diff, instead of writing them.
+    #[arg(long, requires = "in_place")]
+    pub dry_run: bool
+}
+
+// A match found in a line, or in the whole contents in multiline mode, with its replacement if a template was given
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match{
+    pub start: usize,
+    pub end: usize,
+    pub replacement: Option<Vec<u8>>
+}
+
+impl Match{
+    pub fn new(start: usize, end: usize) -> Match{
+        Match{ start, end, replacement: None }
+    }
+}
+
+impl Replace{
+    // The template, if the matches have to be replaced
+    pub fn template(&self) -> Option<&[u8]>{
+        self.replace.as_deref().map(str::as_bytes)
+    }
+
+    // Replaces the matches in the contents of a file. With --dry-run, the changes are returned as an unified diff. Otherwise, the file is written and its name is returned. Nothing is returned if nothing changes
+    pub fn edit(&self, file: &str, contents: &[u8], matches: &[Match]) -> Result<Vec<String>, CluErrors>{
+        if matches.iter().all(|matched| matched.replacement.as_deref() == Some(&contents[matched.start..matched.end])){
+            return Ok(vec![]);
+        }
+        if self.dry_run{
+            return Ok(diff(file, contents, matches));
+        }
+        let (replaced, _positions) = apply(contents, matches);
+        self.write(file, &replaced).map_err(|_error| CluErrors::InputError(format!("{} cannot be written", file)))?;
+        Ok(vec![file.to_string()])
+    }
+
+    // Writes the new contents of a file through a temporary file in its directory, which keeps its permissions and replaces it at once. The file itself is kept with the backup suffix, if any. Symbolic links are kept, as the file they point to is the one written
+    fn write(&self, file: &str, contents: &[u8]) -> std::io::Result<()>{
+        let path = fs::canonicalize(file)?;
+        let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let temporary = path.with_file_name(format!(".{}.clu-{}", name, process::id()));
+        let written = fs::write(&temporary, contents)
+            .and_then(|_| fs::set_permissions(&temporary, fs::metadata(&path)?.permissions()))
+            .and_then(|_| if self.backup_suffix.is_empty(){ Ok(()) } else{ fs::copy(&path, format!("{}{}", path.display(), self.backup_suffix)).map(|_| ()) })
+            .and_then(|_| fs::rename(&temporary, &path));
+        if written.is_err() && Path::new(&temporary).exists(){
+            let _ = fs::remove_file(&temporary);
+        }
+        written
+    }
+}
+
+// Finds the matches in a line, or in the whole contents in multiline mode, expanding the template with the groups captured by each of them
+pub fn find(reg: &Regex, haystack: &[u8], template: Option<&[u8]>) -> Vec<Match>{
+    let mut matches: Vec<Match> = match template{
+        Some(template) => reg
+            .captures_iter(haystack)
+            .map(|captures|{
+                let matched = captures.get(0).unwrap(); // The group 0 is always the whole match
+                let mut replacement = Vec::new();
+                captures.expand(template, &mut replacement);
+                Match{ start: matched.start(), end: matched.end(), replacement: Some(replacement) }
+            })
+            .collect(),
+        None => reg.find_iter(haystack).map(|matched| Match::new(matched.start(), matched.end())).collect()
+    };
+    // After a final line terminator there's no line left, so an empty match there (the last one, if any) isn't part of the selected lines and it's skipped
+    if haystack.ends_with(b"\n") && matches.last().is_some_and(|last| last.start == haystack.len()){
+        matches.pop();
+    }
+    matches
+}
+
+// Replaces the matches of a text, given in order, by their replacements. Returns the new text with the positions of the replaced matches in it, which are the positions of the matches if there are no replacements
+pub fn apply(text: &[u8], matches: &[Match]) -> (Vec<u8>, Vec<(usize, usize)>){
+    let mut replaced = Vec::with_capacity(text.len());
+    let mut positions = Vec::with_capacity(matches.len());
+    let mut last = 0;
+    for matched in matches{
+        replaced.extend_from_slice(&text[last..matched.start]);
+        let start = replaced.len();
+        replaced.extend_from_slice(matched.replacement.as_deref().unwrap_or(&text[matched.start..matched.end]));
+        positions.push((start, replaced.len()));
+        last = matched.end;
+    }
+    replaced.extend_from_slice(&text[last..]);
+    (replaced, positions)
+}
+
+// Describes the replacements as an unified diff without context lines. Every group of lines changed by the same matches is a hunk
+fn diff(file: &str, contents: &[u8], matches: &[Match]) -> Vec<String>{
+    // The offsets where every line starts, and the line where an offset is
+    let starts: Vec<usize> = iter::once(0)
+        .chain(contents.iter().enumerate().filter(|(_position, byte)| **byte == b'\n').map(|(position, _byte)| position + 1))
+        .filter(|&start| start == 0 || start < contents.len())
+        .collect();
+    let line_of = |offset: usize| starts.partition_point(|&start| start <= offset) - 1;
+    // A match removing a line terminator changes the next line too
+    let lines_of = |matched: &Match| (
+        line_of(matched.start),
+        if matched.end > matched.start && contents[matched.end - 1] != b'\n'{ line_of(matched.end - 1) } else{ line_of(matched.end.min(contents.len().saturating_sub(1))) }
+    );
+    let lines = |text: &[u8]| -> Vec<String>{
+        let text = text.strip_suffix(b"\n").unwrap_or(text);
+        text.split(|&byte| byte == b'\n').map(|line| String::from_utf8_lossy(line).to_string()).collect()
+    };
+
+    let mut diff = vec![format!("--- {}", file), format!("+++ {}", file)];
+    let mut added: isize = 0; // Lines added by the previous hunks
+    let mut remaining = matches.iter().filter(|matched| matched.replacement.as_deref() != Some(&contents[matched.start..matched.end])).peekable();
+    while let Some(matched) = remaining.next(){
+        let (first, mut last) = lines_of(matched);
+        let mut hunk = vec![matched.clone()];
+        while let Some(next) = remaining.next_if(|next| lines_of(next).0 <= last){
+            last = last.max(lines_of(next).1);
+            hunk.push(next.clone());
+        }
+        let start = starts[first];
+        let end = starts.get(last + 1).copied().unwrap_or(contents.len());
+        hunk.iter_mut().for_each(|matched|{
+            matched.start -= start;
+            matched.end -= start;
+        });
+        let old = lines(&contents[start..end]);
+        let new = lines(&apply(&contents[start..end], &hunk).0);
+        diff.push(format!("@@ -{},{} +{},{} @@", first + 1, old.len(), first as isize + 1 + added, new.len()));
+        diff.extend(old.iter().map(|line| format!("-{}", line)));
+        diff.extend(new.iter().map(|line| format!("+{}", line)));
+        added += new.len() as isize - old.len() as isize;
+    }
+    diff
+}
+
+// Finds the matches of every line in the contents of a file, with their offsets in the contents
+pub fn find_in_lines(reg: &Regex, contents: &[u8], template: Option<&[u8]>) -> Vec<Match>{
+    let mut matches = Vec::new();
+    let mut remaining = contents;
+    let mut line = Vec::new();
+    let mut offset = 0;
+    while let Ok(read @ 1..) = Grep::read_line(&mut remaining, &mut line){
+        matches.extend(find(reg, &line, template).into_iter().map(|matched| Match{ start: matched.start + offset, end: matched.end + offset, ..matched }));
+        offset += read;
+    }
+    matches
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
@@ -0,0 +1,92 @@
+use std::{env, fs};
+use regex::bytes::Regex;
+use crate::grep::replace::{self, Replace, Match};
+
+// The replacement options of --in-place
+fn in_place(backup_suffix: &str, dry_run: bool) -> Replace{
+    Replace{ replace: Some("$2 $1".to_string()), in_place: true, backup_suffix: backup_suffix.to_string(), dry_run }
+}
+
+#[test]
+fn find_test(){
+    let reg = Regex::new(r"(?P<key>\w+)=(\w+)").unwrap();
+    assert_eq!(vec![Match::new(0, 3), Match::new(4, 7)], replace::find(&reg, b"a=1 b=2", None));
+    assert_eq!(
+        vec![Match{ start: 0, end: 3, replacement: Some(b"1: a $".to_vec()) }, Match{ start: 4, end: 7, replacement: Some(b"2: b $".to_vec()) }],
+        replace::find(&reg, b"a=1 b=2", Some(b"$2: ${key} $$"))
+    );
+    // The groups that don't exist are replaced by nothing
+    assert_eq!(vec![Match{ start: 0, end: 3, replacement: Some(b"[]".to_vec()) }], replace::find(&reg, b"a=1", Some(b"[$3$1a]")));
+
+    // The matches of every line, with their offsets in the contents
+    assert_eq!(
+        vec![Match{ start: 0, end: 3, replacement: Some(b"1".to_vec()) }, Match{ start: 8, end: 11, replacement: Some(b"2".to_vec()) }],
+        replace::find_in_lines(&reg, b"a=1\r\nno\nb=2", Some(b"$2"))
+    );
+
+    // An empty match after the final line terminator isn't in any line, so it isn't replaced
+    let reg = Regex::new("(?m)^").unwrap();
+    assert_eq!(vec![Match::new(0, 0), Match::new(4, 4)], replace::find(&reg, b"one\ntwo\n", None));
+    assert_eq!(vec![Match::new(0, 0), Match::new(4, 4)], replace::find(&reg, b"one\ntwo", None));
+}
+
+#[test]
+fn apply_test(){
+    assert_eq!((b"Hey you".to_vec(), vec![(4, 7)]), replace::apply(b"Hey you", &[Match::new(4, 7)]));
+    let matches = [Match{ start: 0, end: 3, replacement: Some(b"Hello".to_vec()) }, Match{ start: 4, end: 7, replacement: Some(Vec::new()) }];
+    assert_eq!((b"Hello ".to_vec(), vec![(0, 5), (6, 6)]), replace::apply(b"Hey you", &matches));
+    assert_eq!((b"Hey".to_vec(), vec![]), replace::apply(b"Hey", &[]));
+}
+
+#[test]
+fn edit_test(){
+    let file = env::temp_dir().join("amazing_clu_replace_edit_test.txt");
+    let backup = env::temp_dir().join("amazing_clu_replace_edit_test.txt.orig");
+    let path = file.to_str().unwrap();
+    let contents = b"one two\nkeep\nthree four\nfive six\n";
+    fs::write(&file, contents).unwrap();
+    let reg = Regex::new(r"(\w+) (\w+)").unwrap();
+    let matches = replace::find_in_lines(&reg, contents, Some(b"$2 $1"));
+
+    // The dry run describes the changes without writing them
+    assert_eq!(
+        vec![
+            format!("--- {}", path),
+            format!("+++ {}", path),
+            "@@ -1,1 +1,1 @@".to_string(),
+            "-one two".to_string(),
+            "+two one".to_string(),
+            "@@ -3,1 +3,1 @@".to_string(),
+            "-three four".to_string(),
+            "+four three".to_string(),
+            "@@ -4,1 +4,1 @@".to_string(),
+            "-five six".to_string(),
+            "+six five".to_string()
+        ],
+        in_place(".orig", true).edit(path, contents, &matches).unwrap()
+    );
+    assert_eq!(contents.to_vec(), fs::read(&file).unwrap());
+
+    // The file is written, keeping a backup
+    assert_eq!(vec![path.to_string()], in_place(".orig", false).edit(path, contents, &matches).unwrap());
+    assert_eq!(b"two one\nkeep\nfour three\nsix five\n".to_vec(), fs::read(&file).unwrap());
+    assert_eq!(contents.to_vec(), fs::read(&backup).unwrap());
+
+    // Nothing is written if nothing changes
+    let unchanged = replace::find_in_lines(&Regex::new("keep").unwrap(), contents, Some(b"keep"));
+    assert!(in_place("", false).edit(path, contents, &unchanged).unwrap().is_empty());
+    fs::remove_file(file).unwrap();
+    fs::remove_file(backup).unwrap();
+}
+
+#[test]
+fn diff_test(){
+    // A match removing a line terminator joins two lines, so the following hunks are moved
+    let contents = b"a(\n  x)\nb\nc(\n  y)";
+    let reg = Regex::new(r"\(\n\s*").unwrap();
+    let matches = replace::find(&reg, contents, Some(b"("));
+    assert_eq!(
+        vec!["--- file", "+++ file", "@@ -1,2 +1,1 @@", "-a(", "-  x)", "+a(x)", "@@ -4,2 +3,1 @@", "-c(", "-  y)", "+c(y)"],
+        replace::diff("file", contents, &matches)
+    );
+}
\ No newline at end of file
@@ -1,6 +1,30 @@
-use std::{path::PathBuf, collections::HashMap};
-use regex::Regex;
-use crate::{grep::{Grep, context::Context, options::Options, counters::Counters},clu_errors::CluErrors};
+use std::{io, path::PathBuf};
+use regex::bytes::Regex;
+use crate::{base::SortBy, case::{Case, Folding}, walker::{Traversal, Symlinks}, grep::{Grep, Reading, context::Context, options::Options, counters::Counters, binary::{Binary, BinaryFiles}, color::{Color, ColorWhen}, output::{Output, Format, FileOutput}, patterns::Patterns, replace::Replace, limits::{Limits, Budget}},clu_errors::CluErrors};
+
+// An output whose reader went away, as the standard output piped into head once it has read what it needs
+struct ClosedPipe;
+
+impl io::Write for ClosedPipe{
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize>{
+        Err(io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+
+    fn flush(&mut self) -> io::Result<()>{
+        Ok(())
+    }
+}
+
+// Runs the search of a single file, collecting the lines it writes
+fn lines<E>(name: &str, format: &Format, search: impl FnOnce(&mut FileOutput) -> Result<bool, E>) -> Result<Vec<String>, E>{
+    let mut found = Vec::new();
+    let mut emit = |line: String|{
+        found.push(line.trim_end_matches('\n').to_string());
+        true
+    };
+    search(&mut FileOutput::new(name, format, 0, &mut emit))?;
+    Ok(found)
+}
 
 #[test] // The errors are propagated from the single_file function, except the one creating the Regex. Let's test that one, a successful case without case insensitive flag set on and a successful case with the case insensitive flag set on.
 fn execute_multiple_files_grep_test(){
@@ -12,49 +36,95 @@ fn execute_multiple_files_grep_test(){
     pathbuf.push("sample_text.txt");
     pathbuf2.push(".hidden_text.txt");
 
+    // Errors found in single files are collected here
+    let mut errors = Vec::new();
+
     // Regex error
     let grep = Grep{
-        pattern: Box::new("[a-z+".to_string()),
+        pattern: Some("[a-z+".to_string()),
         path: vec![],
         dereference_recursive: false,
-        ignore_case: false,
+        case: Case{ ignore_case: false, smart_case: false, no_ignore_case: false, case_folding: Folding::Unicode },
         hidden_items: false,
+        threads: 4,
+        traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false },
+        sort: SortBy::None,
+        no_messages: false,
+        patterns: Patterns{ regexp: vec![], file: vec![], fixed_strings: false, extended_regexp: false, basic_regexp: false, word_regexp: false, line_regexp: false, multiline: false, multiline_dotall: false },
+        binary: Binary { binary_files: BinaryFiles::Binary, text: false },
+        color: Color { color: ColorWhen::Never },
+        output: Output { with_filename: false, no_filename: false, group_separator: "--".to_string(), no_group_separator: false, byte_offset: false, column: false, vimgrep: false, json: false, help: None },
+        replace: Replace{ replace: None, in_place: false, backup_suffix: ".bak".to_string(), dry_run: false },
+        limits: Limits{ max_count: None, max_total: None, quiet: false },
         context: Context { after_context: 0, before_context: 0, context: 0 },
-        options: Options { files_with_matches: false, line_number: false, invert_match: false, only_matching: false},
+        options: Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false},
         counters: Counters { count: true, total_count: false }
     };
 
-    assert_eq!(CluErrors::RegexError(grep.pattern.to_string()), grep.execute_multiple_files_grep(vec![pathbuf.to_str().unwrap().to_string(), pathbuf2.to_str().unwrap().to_string()]).unwrap_err());
+    assert_eq!(CluErrors::RegexError("[a-z+".to_string()), grep.execute_multiple_files_grep(vec![pathbuf.to_str().unwrap().to_string(), pathbuf2.to_str().unwrap().to_string()], true, false, &mut Vec::new(), &mut errors).unwrap_err());
 
     // No case insensitive succeed
     let grep = Grep{
-        pattern: Box::new("Grep".to_string()),
+        pattern: Some("Grep".to_string()),
         path: vec![],
         dereference_recursive: false,
-        ignore_case: false,
+        case: Case{ ignore_case: false, smart_case: false, no_ignore_case: false, case_folding: Folding::Unicode },
         hidden_items: false,
+        threads: 4,
+        traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false },
+        sort: SortBy::None,
+        no_messages: false,
+        patterns: Patterns{ regexp: vec![], file: vec![], fixed_strings: false, extended_regexp: false, basic_regexp: false, word_regexp: false, line_regexp: false, multiline: false, multiline_dotall: false },
+        binary: Binary { binary_files: BinaryFiles::Binary, text: false },
+        color: Color { color: ColorWhen::Never },
+        output: Output { with_filename: false, no_filename: false, group_separator: "--".to_string(), no_group_separator: false, byte_offset: false, column: false, vimgrep: false, json: false, help: None },
+        replace: Replace{ replace: None, in_place: false, backup_suffix: ".bak".to_string(), dry_run: false },
+        limits: Limits{ max_count: None, max_total: None, quiet: false },
         context: Context { after_context: 0, before_context: 0, context: 0 },
-        options: Options { files_with_matches: false, line_number: false, invert_match: false, only_matching: false},
+        options: Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false},
         counters: Counters { count: true, total_count: false }
     };
-    let executed = grep.execute_multiple_files_grep(vec![pathbuf.to_str().unwrap().to_string(), pathbuf2.to_str().unwrap().to_string()]).unwrap(); // As it comes from threads we cannot ensure the disposition, however we can ensure that it contains 0 twice due to the search is Case sensitive and the files don't contain Grep. We also can ensure the length is 4.
-    assert_eq!(4, executed.len());
-    assert_eq!(2, executed.iter().filter(|item| **item == "0".to_string()).count());
+    let mut out = Vec::new();
+    assert!(!grep.execute_multiple_files_grep(vec![pathbuf.to_str().unwrap().to_string(), pathbuf2.to_str().unwrap().to_string()], true, false, &mut out, &mut errors).unwrap()); // Even if it comes from threads, the results keep the order of the paths. It contains 0 twice due to the search is Case sensitive and the files don't contain Grep, so nothing is found although the counts are written.
+    assert_eq!(format!("{}:0\n{}:0\n", pathbuf.to_str().unwrap(), pathbuf2.to_str().unwrap()), String::from_utf8(out).unwrap());
 
     // Same test but case insensitive will contain 1 twice
     let grep = Grep{
-        pattern: Box::new("Grep".to_string()),
+        pattern: Some("Grep".to_string()),
         path: vec![],
         dereference_recursive: false,
-        ignore_case: true,
+        case: Case{ ignore_case: true, smart_case: false, no_ignore_case: false, case_folding: Folding::Unicode },
         hidden_items: false,
+        threads: 4,
+        traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false },
+        sort: SortBy::None,
+        no_messages: false,
+        patterns: Patterns{ regexp: vec![], file: vec![], fixed_strings: false, extended_regexp: false, basic_regexp: false, word_regexp: false, line_regexp: false, multiline: false, multiline_dotall: false },
+        binary: Binary { binary_files: BinaryFiles::Binary, text: false },
+        color: Color { color: ColorWhen::Never },
+        output: Output { with_filename: false, no_filename: false, group_separator: "--".to_string(), no_group_separator: false, byte_offset: false, column: false, vimgrep: false, json: false, help: None },
+        replace: Replace{ replace: None, in_place: false, backup_suffix: ".bak".to_string(), dry_run: false },
+        limits: Limits{ max_count: None, max_total: None, quiet: false },
         context: Context { after_context: 0, before_context: 0, context: 0 },
-        options: Options { files_with_matches: false, line_number: false, invert_match: false, only_matching: false},
+        options: Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false},
         counters: Counters { count: true, total_count: false }
     };
-    let executed = grep.execute_multiple_files_grep(vec![pathbuf.to_str().unwrap().to_string(), pathbuf2.to_str().unwrap().to_string()]).unwrap(); // As it comes from threads we cannot ensure the disposition, however we can ensure that it contains 0 twice due to the search is Case sensitive and the files don't contain Grep. We also can ensure the length is 4.
-    assert_eq!(4, executed.len());
-    assert_eq!(2, executed.iter().filter(|item| **item == "1".to_string()).count());
+    let mut out = Vec::new();
+    assert!(grep.execute_multiple_files_grep(vec![pathbuf2.to_str().unwrap().to_string(), pathbuf.to_str().unwrap().to_string()], true, false, &mut out, &mut errors).unwrap()); // Swapping the paths swaps the results
+    assert_eq!(format!("{}:1\n{}:1\n", pathbuf2.to_str().unwrap(), pathbuf.to_str().unwrap()), String::from_utf8(out).unwrap());
+
+    // A file that cannot be read doesn't discard the results of the rest of files
+    let mut pathbuf3 = PathBuf::new();
+    pathbuf3.push(".");
+    pathbuf3.push("tests");
+    pathbuf3.push("text.txt");
+    let mut out = Vec::new();
+    assert!(grep.execute_multiple_files_grep(vec![pathbuf.to_str().unwrap().to_string(), pathbuf3.to_str().unwrap().to_string()], true, false, &mut out, &mut errors).unwrap());
+    assert_eq!(format!("{}:1\n", pathbuf.to_str().unwrap()), String::from_utf8(out).unwrap());
+    assert_eq!(vec![CluErrors::InputError(format!("{} cannot be read", pathbuf3.to_str().unwrap()))], errors);
+
+    // An output that cannot be written stops the search
+    assert_eq!(CluErrors::BrokenPipe, grep.execute_multiple_files_grep(vec![pathbuf.to_str().unwrap().to_string()], true, false, &mut ClosedPipe, &mut Vec::new()).unwrap_err());
 
 }
 
@@ -66,14 +136,14 @@ fn execute_single_file_grep_test(){
     let mut pathbuf = PathBuf::new();
     pathbuf.push(".");
     pathbuf.push("tests");
-    assert_eq!(CluErrors::InputError(format!("{} is dir. If you want to use grep recursively in a directory, add the -R flag. For more information try --help",pathbuf.to_str().unwrap().to_string())), Grep::execute_single_file_grep(reg.clone(), &pathbuf.to_str().unwrap().to_string(),0,0,0,false,false,false,false,false,false).unwrap_err());
+    assert_eq!(CluErrors::InputError(format!("{} is dir. If you want to use grep recursively in a directory, add the -R flag. For more information try --help",pathbuf.to_str().unwrap())), lines(pathbuf.to_str().unwrap(), &Format::plain(), |output| Grep::execute_single_file_grep(reg.clone(), pathbuf.to_str().unwrap(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, Counters { count: false, total_count: false }, Reading{ binary_files: BinaryFiles::Binary, multiline: false }, output)).unwrap_err());
 
     // Trying to read something unexistent
     let mut pathbuf = PathBuf::new();
     pathbuf.push(".");
     pathbuf.push("tests");
     pathbuf.push("text.txt");
-    assert_eq!(CluErrors::InputError(format!("{} cannot be read",pathbuf.to_str().unwrap().to_string())), Grep::execute_single_file_grep(reg.clone(), &pathbuf.to_str().unwrap().to_string(),0,0,0,false,false,false,false,false,false).unwrap_err());
+    assert_eq!(CluErrors::InputError(format!("{} cannot be read",pathbuf.to_str().unwrap())), lines(pathbuf.to_str().unwrap(), &Format::plain(), |output| Grep::execute_single_file_grep(reg.clone(), pathbuf.to_str().unwrap(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, Counters { count: false, total_count: false }, Reading{ binary_files: BinaryFiles::Binary, multiline: false }, output)).unwrap_err());
 
     // Successful call
     let mut pathbuf = PathBuf::new();
@@ -81,7 +151,24 @@ fn execute_single_file_grep_test(){
     pathbuf.push("tests");
     pathbuf.push("grep_files");
     pathbuf.push("sample_text.txt");
-    assert_eq!(vec!["2-How are you? Who are you?".to_string(), "3:I'm grep".to_string(), "4-Nice to meet you".to_string()], Grep::execute_single_file_grep(reg.clone(), &pathbuf.to_str().unwrap().to_string(),0,0,1,false,true,false,false,false,false).unwrap());
+    assert_eq!(vec!["2-How are you? Who are you?".to_string(), "3:I'm grep".to_string(), "4-Nice to meet you".to_string()], lines(pathbuf.to_str().unwrap(), &Format::plain(), |output| Grep::execute_single_file_grep(reg.clone(), pathbuf.to_str().unwrap(), Context { after_context: 0, before_context: 0, context: 1 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, Counters { count: false, total_count: false }, Reading{ binary_files: BinaryFiles::Binary, multiline: false }, output)).unwrap());
+
+    // -L lists the file if it doesn't match. With -c, -v counts the lines without the pattern and -l skips the files counting 0
+    assert!(lines(pathbuf.to_str().unwrap(), &Format::plain(), |output| Grep::execute_single_file_grep(reg.clone(), pathbuf.to_str().unwrap(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: true, line_number: false, invert_match: false, only_matching: false}, Counters { count: false, total_count: false }, Reading{ binary_files: BinaryFiles::Binary, multiline: false }, output)).unwrap().is_empty());
+    assert_eq!(vec![pathbuf.to_str().unwrap().to_string()], lines(pathbuf.to_str().unwrap(), &Format::plain(), |output| Grep::execute_single_file_grep(Regex::new("missing").unwrap(), pathbuf.to_str().unwrap(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: true, line_number: false, invert_match: false, only_matching: false}, Counters { count: false, total_count: false }, Reading{ binary_files: BinaryFiles::Binary, multiline: false }, output)).unwrap());
+    assert_eq!(vec!["3".to_string()], lines(pathbuf.to_str().unwrap(), &Format::plain(), |output| Grep::execute_single_file_grep(reg.clone(), pathbuf.to_str().unwrap(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: true, only_matching: false}, Counters { count: true, total_count: false }, Reading{ binary_files: BinaryFiles::Binary, multiline: false }, output)).unwrap());
+    assert!(lines(pathbuf.to_str().unwrap(), &Format::plain(), |output| Grep::execute_single_file_grep(Regex::new("missing").unwrap(), pathbuf.to_str().unwrap(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: true, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, Counters { count: true, total_count: false }, Reading{ binary_files: BinaryFiles::Binary, multiline: false }, output)).unwrap().is_empty());
+    // Binary files. By default, grep just reports if they match. They can be also skipped or treated as text
+    let mut pathbuf = PathBuf::new();
+    pathbuf.push(".");
+    pathbuf.push("tests");
+    pathbuf.push("grep_files");
+    pathbuf.push("binary_file.bin");
+    assert_eq!(vec![format!("Binary file {} matches", pathbuf.to_str().unwrap())], lines(pathbuf.to_str().unwrap(), &Format::plain(), |output| Grep::execute_single_file_grep(reg.clone(), pathbuf.to_str().unwrap(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, Counters { count: false, total_count: false }, Reading{ binary_files: BinaryFiles::Binary, multiline: false }, output)).unwrap());
+    assert!(lines(pathbuf.to_str().unwrap(), &Format::plain(), |output| Grep::execute_single_file_grep(reg.clone(), pathbuf.to_str().unwrap(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, Counters { count: false, total_count: false }, Reading{ binary_files: BinaryFiles::WithoutMatch, multiline: false }, output)).unwrap().is_empty());
+    assert_eq!(vec!["2:second line with grep".to_string()], lines(pathbuf.to_str().unwrap(), &Format::plain(), |output| Grep::execute_single_file_grep(Regex::new("second").unwrap(), pathbuf.to_str().unwrap(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, Counters { count: false, total_count: false }, Reading{ binary_files: BinaryFiles::Text, multiline: false }, output)).unwrap());
+    // Counters work as usual in binary files
+    assert_eq!(vec!["2".to_string()], lines(pathbuf.to_str().unwrap(), &Format::plain(), |output| Grep::execute_single_file_grep(reg.clone(), pathbuf.to_str().unwrap(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, Counters { count: true, total_count: false }, Reading{ binary_files: BinaryFiles::Binary, multiline: false }, output)).unwrap());
 }
 
 #[test]
@@ -89,37 +176,97 @@ fn search_test(){
     let reg = Regex::new("grep").unwrap();
     let contents = "Hey you\nHow are you? Who are you?\nI'm grep\nNice to meet you";
     // Without flags
-    assert_eq!(vec!["I'm grep".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 0, 0, false, false, false));
+    assert_eq!(vec!["I'm grep".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, output)).unwrap());
     // Before context to 1
-    assert_eq!(vec!["How are you? Who are you?".to_string(), "I'm grep".to_string()],Grep::search(reg.clone(), contents.to_string(), 1, 0, 0, false, false, false));
+    assert_eq!(vec!["How are you? Who are you?".to_string(), "I'm grep".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 1, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, output)).unwrap());
     // After context to 1
-    assert_eq!(vec!["I'm grep".to_string(), "Nice to meet you".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 1, 0, false, false, false));
+    assert_eq!(vec!["I'm grep".to_string(), "Nice to meet you".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 1, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, output)).unwrap());
     // Before and after contect to 2
-    assert_eq!(contents.split("\n").map(|item|item.to_string()).collect::<Vec<String>>(),Grep::search(reg.clone(), contents.to_string(), 2, 2, 0, false, false, false));
+    assert_eq!(contents.split("\n").map(|item|item.to_string()).collect::<Vec<String>>(),lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 2, before_context: 2, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, output)).unwrap());
     // Context to 1
-    assert_eq!(vec!["How are you? Who are you?".to_string(), "I'm grep".to_string(), "Nice to meet you".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 0, 1, false, false, false));
+    assert_eq!(vec!["How are you? Who are you?".to_string(), "I'm grep".to_string(), "Nice to meet you".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 1 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, output)).unwrap());
     // Before and after context to 2 but overriden by context to 1
-    assert_eq!(vec!["How are you? Who are you?".to_string(), "I'm grep".to_string(), "Nice to meet you".to_string()],Grep::search(reg.clone(), contents.to_string(), 2, 2, 1, false, false, false));
+    assert_eq!(vec!["How are you? Who are you?".to_string(), "I'm grep".to_string(), "Nice to meet you".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 2, before_context: 2, context: 1 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, output)).unwrap());
     // Line number flag activated
-    assert_eq!(vec!["3:I'm grep".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 0, 0, true, false, false));
+    assert_eq!(vec!["3:I'm grep".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, output)).unwrap());
     // Line number flag + context
-    assert_eq!(vec!["2-How are you? Who are you?".to_string(), "3:I'm grep".to_string(), "4-Nice to meet you".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 0, 1, true, false, false));
+    assert_eq!(vec!["2-How are you? Who are you?".to_string(), "3:I'm grep".to_string(), "4-Nice to meet you".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 1 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, output)).unwrap());
     // Invert match flag activated
-    assert_eq!(vec!["Hey you".to_string(),"How are you? Who are you?".to_string(), "Nice to meet you".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 0, 0, false, true, false));
+    assert_eq!(vec!["Hey you".to_string(),"How are you? Who are you?".to_string(), "Nice to meet you".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: true, only_matching: false}, output)).unwrap());
     // Line number + invert_match
-    assert_eq!(vec!["1:Hey you".to_string(),"2:How are you? Who are you?".to_string(), "4:Nice to meet you".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 0, 0, true, true, false));
+    assert_eq!(vec!["1:Hey you".to_string(),"2:How are you? Who are you?".to_string(), "4:Nice to meet you".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: true, only_matching: false}, output)).unwrap());
     // Line number + invert_match + context
-    assert_eq!(vec!["1:Hey you".to_string(),"2:How are you? Who are you?".to_string(), "3-I'm grep".to_string(), "4:Nice to meet you".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 0, 1, true, true, false));
+    assert_eq!(vec!["1:Hey you".to_string(),"2:How are you? Who are you?".to_string(), "3-I'm grep".to_string(), "4:Nice to meet you".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 1 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: true, only_matching: false}, output)).unwrap());
     // Only_matching flag
-    assert_eq!(vec!["grep".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 0, 0, false, false, true));
+    assert_eq!(vec!["grep".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: true}, output)).unwrap());
     // Only_matching + context
-    assert_eq!(vec!["How are you? Who are you?".to_string(),"grep".to_string(),"Nice to meet you".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 0, 1, false, false, true));
+    assert_eq!(vec!["How are you? Who are you?".to_string(),"grep".to_string(),"Nice to meet you".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 1 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: true}, output)).unwrap());
     // Only_matching + line_number
-    assert_eq!(vec!["3:grep".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 0, 0, true, false, true));
+    assert_eq!(vec!["3:grep".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: true}, output)).unwrap());
     // Only_matching + context + line_number
-    assert_eq!(vec!["2-How are you? Who are you?".to_string(),"3:grep".to_string(),"4-Nice to meet you".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 0, 1, true, false, true));
+    assert_eq!(vec!["2-How are you? Who are you?".to_string(),"3:grep".to_string(),"4-Nice to meet you".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 1 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: true}, output)).unwrap());
     // Only_matching + invert_match -> Invert match override only_matching
-    assert_eq!(vec!["Hey you".to_string(),"How are you? Who are you?".to_string(), "Nice to meet you".to_string()],Grep::search(reg.clone(), contents.to_string(), 0, 0, 0, false, true, true));
+    assert_eq!(vec!["Hey you".to_string(),"How are you? Who are you?".to_string(), "Nice to meet you".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: true, only_matching: true}, output)).unwrap());
+    // Several matches whose contexts overlap or are apart. Each context line is printed just once and the lines out of every context are skipped
+    let contents = "grep\none\ntwo\nthree\ngrep\nfour\ngrep\nfive\nsix";
+    assert_eq!(vec!["1:grep".to_string(), "2-one".to_string(), "4-three".to_string(), "5:grep".to_string(), "6-four".to_string(), "7:grep".to_string(), "8-five".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 1, before_context: 1, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, output)).unwrap());
+    // Non UTF-8 contents don't make the search fail
+    assert_eq!(vec!["I'm grep \u{FFFD}".to_string()],lines("file", &Format::plain(), |output| Grep::search(reg.clone(), b"\xff\nI'm grep \xfe".as_slice(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, output)).unwrap());
+
+    // Groups of lines that aren't next to each other are separated, and the lines are prefixed with their file as in GNU grep
+    let contents = "grep\none\ntwo\nthree\ngrep\nfour\nfive\nsix\ngrep";
+    let format = Format{ with_filename: true, group_separator: Some("--".to_string()), ..Format::plain() };
+    assert_eq!(
+        vec!["file:1:grep", "file-2-one", "--", "file-4-three", "file:5:grep", "file-6-four", "--", "file-8-six", "file:9:grep"],
+        lines("file", &format, |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 1, before_context: 1, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, output)).unwrap()
+    );
+    let format = Format{ group_separator: None, ..format };
+    assert_eq!(
+        vec!["file:grep", "file:grep", "file:grep"],
+        lines("file", &format, |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, output)).unwrap()
+    );
+
+    // JSON output: the lines of the file are wrapped by its begin and end objects, -o is ignored as the matches are already reported
+    let format = Format{ json: true, ..Format::plain() };
+    assert_eq!(
+        vec![
+            r#"{"type":"begin","data":{"path":{"text":"file"}}}"#,
+            r#"{"type":"context","data":{"path":{"text":"file"},"lines":{"text":"How are you? Who are you?"},"line_number":2,"absolute_offset":8,"submatches":[]}}"#,
+            r#"{"type":"match","data":{"path":{"text":"file"},"lines":{"text":"I'm grep"},"line_number":3,"absolute_offset":34,"submatches":[{"match":{"text":"grep"},"start":4,"end":8}]}}"#,
+            r#"{"type":"end","data":{"path":{"text":"file"},"stats":{"matched_lines":1,"matches":1,"bytes_searched":59}}}"#
+        ],
+        lines("file", &format, |output| Grep::search(reg.clone(), "Hey you\nHow are you? Who are you?\nI'm grep\nNice to meet you".as_bytes(), Context { after_context: 0, before_context: 1, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: true}, output)).unwrap()
+    );
+    assert!(lines("file", &format, |output| Grep::search(reg.clone(), "Hey you".as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: false, invert_match: false, only_matching: false}, output)).unwrap().is_empty());
+
+    // With -m, the file is read until the after context of the last line allowed, which ends at the next line that would be selected
+    let contents = "grep\none\ngrep\ntwo\nthree\ngrep";
+    let format = Format{ max_count: Some(2), ..Format::plain() };
+    assert_eq!(vec!["1:grep", "2-one", "3:grep", "4-two"], lines("file", &format, |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 1, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, output)).unwrap());
+    let format = Format{ max_count: Some(1), ..Format::plain() };
+    assert_eq!(vec!["1:grep", "2-one"], lines("file", &format, |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 3, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, output)).unwrap());
+    let format = Format{ max_count: Some(0), ..Format::plain() };
+    assert!(lines("file", &format, |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 1, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, output)).unwrap().is_empty());
+
+    // The --max-total budget is shared by every file searched
+    let format = Format{ budget: Budget::new(Some(3)), ..Format::plain() };
+    assert_eq!(vec!["1:grep", "3:grep"], lines("file", &Format{ max_count: Some(2), ..format }, |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, output)).unwrap());
+    let format = Format{ budget: Budget::new(Some(3)), ..Format::plain() };
+    assert_eq!(vec!["1:grep", "2-one", "3:grep", "4-two", "6:grep"], lines("file", &format, |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 1, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, output)).unwrap());
+    assert!(format.budget.is_exhausted());
+    assert!(lines("file", &format, |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 1, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, output)).unwrap().is_empty());
+
+    // The columns and the byte offsets are written after the line numbers. With -o, they're the ones of every match
+    let contents = "Hey you\nHow are you? Who are you?";
+    let reg = Regex::new("are").unwrap();
+    let format = Format{ byte_offset: true, column: true, ..Format::plain() };
+    assert_eq!(vec!["2:5:8:How are you? Who are you?"], lines("file", &format, |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, output)).unwrap());
+    // A selected line without a match is written at the column 1
+    assert_eq!(vec!["1:1:0:Hey you"], lines("file", &format, |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: true, only_matching: false}, output)).unwrap());
+    assert_eq!(vec!["2:5:12:are", "2:18:25:are"], lines("file", &format, |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: true}, output)).unwrap());
+    // With --vimgrep, the line is written once per match
+    let format = Format{ with_filename: true, column: true, vimgrep: true, ..Format::plain() };
+    assert_eq!(vec!["file:2:5:How are you? Who are you?", "file:2:18:How are you? Who are you?"], lines("file", &format, |output| Grep::search(reg.clone(), contents.as_bytes(), Context { after_context: 0, before_context: 0, context: 0 }, Options { files_with_matches: false, files_without_match: false, line_number: true, invert_match: false, only_matching: false}, output)).unwrap());
 }
 
 #[test]
@@ -128,8 +275,13 @@ fn count_test(){
     let reg1 = Regex::new("you").unwrap();
     let reg2 = Regex::new(r"[A-Z][a-z]").unwrap();
 
-    assert_eq!(vec![String::from("3")], Grep::count(reg1, contents.to_string()));
-    assert_eq!(vec![String::from("3")], Grep::count(reg2, contents.to_string()));
+    assert_eq!(3, Grep::count(reg1.clone(), contents.as_bytes(), false, None).unwrap());
+    assert_eq!(3, Grep::count(reg2, contents.as_bytes(), false, None).unwrap());
+    // With -m, the lines are counted up to the maximum
+    assert_eq!(2, Grep::count(reg1.clone(), contents.as_bytes(), false, Some(2)).unwrap());
+    assert_eq!(3, Grep::count(reg1.clone(), contents.as_bytes(), false, Some(5)).unwrap());
+    // With -v, the lines without the pattern are counted
+    assert_eq!(1, Grep::count(reg1, contents.as_bytes(), true, None).unwrap());
 }
 
 #[test]
@@ -137,8 +289,11 @@ fn total_count_test(){
     let contents = "Hey you\nHow are you? Who are you?\nI'm grep\nNice to meet you";
     let reg1 = Regex::new("you").unwrap();
     let reg2 = Regex::new(r"[A-Z][a-z]").unwrap();
-    assert_eq!(vec![String::from("4")], Grep::total_count(reg1, contents.to_string()));
-    assert_eq!(vec![String::from("4")], Grep::total_count(reg2, contents.to_string()));
+    assert_eq!(4, Grep::total_count(reg1.clone(), contents.as_bytes(), None).unwrap());
+    assert_eq!(4, Grep::total_count(reg2, contents.as_bytes(), None).unwrap());
+    // With -m, only the matches in the first lines containing the pattern are counted
+    assert_eq!(3, Grep::total_count(reg1.clone(), contents.as_bytes(), Some(2)).unwrap());
+    assert_eq!(0, Grep::total_count(reg1, contents.as_bytes(), Some(0)).unwrap());
 }
 
 #[test]
@@ -146,80 +301,40 @@ fn files_with_matches_test(){
     let contents = "Hey you\nHow are you? Who are you?\nI'm grep\nNice to meet you";
     let contents2 = "Hey";
     let reg = Regex::new("you").unwrap();
-    assert_eq!(true, Grep::files_with_matches(reg.clone(), contents.to_string(), false));
-    assert_eq!(false, Grep::files_with_matches(reg.clone(), contents.to_string(), true));
-    assert_eq!(false, Grep::files_with_matches(reg.clone(), contents2.to_string(), false));
-    assert_eq!(true, Grep::files_with_matches(reg, contents2.to_string(), true));
+    assert!(Grep::files_with_matches(reg.clone(), contents.as_bytes(), false).unwrap());
+    assert!(!Grep::files_with_matches(reg.clone(), contents.as_bytes(), true).unwrap());
+    assert!(!Grep::files_with_matches(reg.clone(), contents2.as_bytes(), false).unwrap());
+    assert!(Grep::files_with_matches(reg, contents2.as_bytes(), true).unwrap());
 }
 
 #[test]
-fn context_lines_test(){
-    let contents = "Hey you\nHow are you? Who are you?\nI'm grep\nNice to meet you";
-    let filtered_lines = vec![(2 as usize, "I'm grep")];
-
-    // Before test
-    assert_eq!(HashMap::from([(1 as usize, "How are you? Who are you?")]), Grep::context_lines(contents, &filtered_lines, 0, 1, 4));
-    // After test
-    assert_eq!(HashMap::from([(3 as usize, "Nice to meet you")]), Grep::context_lines(contents, &filtered_lines, 1, 0, 4));
-    // Before + after test
-    assert_eq!(HashMap::from([(0,"Hey you"),(1, "How are you? Who are you?"),(3, "Nice to meet you")]), Grep::context_lines(contents, &filtered_lines, 1, 2, 4));
+fn read_line_test(){
+    // Lines are read without their terminator, no matter if it's \n or \r\n, and the last line doesn't need a terminator. Non UTF-8 contents are read as well
+    let mut contents = b"Hey you\r\nHow are you?\n\xffI'm grep".as_slice();
+    let mut line = Vec::new();
+    assert_eq!(9, Grep::read_line(&mut contents, &mut line).unwrap()); // The bytes read include the terminator
+    assert_eq!(b"Hey you".to_vec(), line);
+    assert_eq!(13, Grep::read_line(&mut contents, &mut line).unwrap());
+    assert_eq!(b"How are you?".to_vec(), line);
+    assert_eq!(9, Grep::read_line(&mut contents, &mut line).unwrap());
+    assert_eq!(b"\xffI'm grep".to_vec(), line);
+    assert_eq!(0, Grep::read_line(&mut contents, &mut line).unwrap());
 }
 
 #[test]
-fn output_search_lines_test(){
-    assert_eq!("Hey".to_string(),Grep::output_search_lines(0, "Hey", false, false));
-    assert_eq!("Hey".to_string(),Grep::output_search_lines(0, "Hey", true, false));
-    assert_eq!("1:Hey".to_string(),Grep::output_search_lines(0, "Hey", false, true));
-    assert_eq!("1-Hey".to_string(),Grep::output_search_lines(0, "Hey", true, true));
+fn is_binary_test(){
+    let mut text = b"Hey you\nI'm grep".as_slice();
+    let mut binary = b"Hey you\n\0I'm grep".as_slice();
+    assert!(!Grep::is_binary(&mut text).unwrap());
+    assert!(Grep::is_binary(&mut binary).unwrap());
+    // The contents are just peeked, so they're still there to be read
+    let mut line = Vec::new();
+    assert_eq!(8, Grep::read_line(&mut binary, &mut line).unwrap());
+    assert_eq!(b"Hey you".to_vec(), line);
 }
 
-#[test] // This test may be an integration test as it relies on the module base, however as it uses a private function we have to run it as an unit test
-fn validate_commands_test(){
-    let no_options_no_counters = Grep{
-        pattern: Box::new(String::new()),
-        path: vec![],
-        dereference_recursive: false,
-        ignore_case: false,
-        hidden_items: false,
-        context: Context { after_context: 0, before_context: 0, context: 0 },
-        options: Options { files_with_matches: false, line_number: false, invert_match: false, only_matching: false},
-        counters: Counters { count: false, total_count: false }
-    };
-    let only_options = Grep{
-        pattern: Box::new(String::new()),
-        path: vec![],
-        dereference_recursive: false,
-        ignore_case: false,
-        hidden_items: false,
-        context: Context { after_context: 0, before_context: 0, context: 0 },
-        options: Options { files_with_matches: true, line_number: false, invert_match: true, only_matching: true},
-        counters: Counters { count: false, total_count: false }
-    };
-
-    let only_counters = Grep{
-        pattern: Box::new(String::new()),
-        path: vec![],
-        dereference_recursive: false,
-        ignore_case: false,
-        hidden_items: false,
-        context: Context { after_context: 0, before_context: 0, context: 0 },
-        options: Options { files_with_matches: false, line_number: false, invert_match: false, only_matching: false},
-        counters: Counters { count: true, total_count: false }
-    };
-
-    let both_options_and_counters = Grep{
-        pattern: Box::new(String::new()),
-        path: vec![],
-        dereference_recursive: false,
-        ignore_case: false,
-        hidden_items: false,
-        context: Context { after_context: 0, before_context: 0, context: 0 },
-        options: Options { files_with_matches: true, line_number: false, invert_match: true, only_matching: true},
-        counters: Counters { count: true, total_count: false }
-    };
-    
-    assert!(no_options_no_counters.validate_commands());
-    assert!(only_options.validate_commands());
-    assert!(only_counters.validate_commands());
-    assert!(!both_options_and_counters.validate_commands());
-}    
\ No newline at end of file
+#[test]
+fn display_name_test(){
+    assert_eq!("(standard input)", Grep::display_name("-"));
+    assert_eq!("sample_text.txt", Grep::display_name("sample_text.txt"));
+}
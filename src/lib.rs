@@ -1,29 +1,47 @@
 use clap::{Parser, Subcommand};
+use std::{env, ffi::OsString, io::{self, IsTerminal, Write}, process::ExitCode};
 
 mod clu_errors;
 mod base;
 mod grep;
 mod find;
-use clu_errors::CluErrors;
+mod pool;
+mod walker;
+mod case;
+pub use clu_errors::{CluErrors, EXIT_SUCCESS, EXIT_NOT_FOUND, EXIT_ERROR};
 
 #[derive(Subcommand)]
 enum Utility{
-    /// Grep: With grep, you can search for a regular expression contained in a specified file path. By default, grep ignores hidden files and directories (whose names start with .), but you can tell grep to also look into them by adding the command flag -H. To check out all the possibilities offered by this implementation try amazing_clu grep --help.
+    /// Grep: With grep, you can search for a regular expression contained in a specified file path. By default, grep ignores hidden files and directories (whose names start with .), but you can tell grep to also look into them by adding the command flag --hidden-items. The results are printed as GNU grep does, eg: file:line:text. To check out all the possibilities offered by this implementation try amazing_clu grep --help.
     Grep(grep::Grep),
     
-    /// Find:
+    /// Find: With find, you can look for the files and directories in the specified paths that satisfy an expression, eg: find . -name '*.txt' -size +1k. By default, find ignores hidden files and directories (whose names start with .), but you can tell find to also look into them by adding the command flag -H. To check out all the possibilities offered by this implementation try amazing_clu find --help.
     Find(find::Find)
 }
 
 impl Utility{
-    fn execute(self) -> Result<String,CluErrors>{
+    // terminal tells if out is a terminal, which the utilities can't guess from out itself
+    fn execute(self, out: &mut dyn Write, terminal: bool) -> Result<Execution,CluErrors>{
         match self{
-            Self::Grep(grep) => grep.execute(),
-            Self::Find(find)=> find.execute()
+            Self::Grep(grep) => grep.execute(out, terminal),
+            Self::Find(find)=> find.execute(out)
         }
     }
 }
 
+/// Result of a successful utility execution, whose output has been already written. Some items (eg, an unreadable file among many others) may have failed without stopping the execution, in that case the errors have been already reported in the standard error and errors_occurred is set.
+#[derive(Debug)]
+pub struct Execution{
+    pub errors_occurred: bool
+}
+
+impl Execution{
+    /// Exit status of the program when the execution succeeds: EXIT_SUCCESS, unless some errors occurred along the way, which is EXIT_ERROR.
+    pub fn exit_code(&self) -> u8{
+        if self.errors_occurred { EXIT_ERROR } else { EXIT_SUCCESS }
+    }
+}
+
 #[derive(Parser)]
 #[command(author = "Tomás Senovilla", version = "0.1.0", about = "Small clu utilities project", long_about = None)]
 pub struct Clu{
@@ -32,12 +50,54 @@ pub struct Clu{
 }
 
 impl Clu{
-    pub fn run() -> Result<String, CluErrors>{
-        Ok(
-            Self::parse()
-                .utility
-                .ok_or(CluErrors::MissingCommand)?
-                .execute()?
-        )
+    /// Parses the command line, executes the requested utility and prints its output in the standard output as it's produced. The errors are printed in the standard error. The returned exit code follows the POSIX convention: EXIT_SUCCESS if the execution found something, EXIT_NOT_FOUND if it didn't (printing nothing but the counts of 0, if requested) and EXIT_ERROR if something failed. If the standard output is closed before the end (eg, the output is piped into head), the execution stops quietly with EXIT_SUCCESS.
+    pub fn run() -> ExitCode{
+        let stdout = io::stdout();
+        match Self::execute_into(env::args_os(), &mut stdout.lock(), stdout.is_terminal()){
+            Ok(execution) => ExitCode::from(execution.exit_code()),
+            Err(CluErrors::BrokenPipe) => ExitCode::from(EXIT_SUCCESS),
+            Err(CluErrors::NotFoundError) => ExitCode::from(EXIT_NOT_FOUND), // As in grep, an empty research exits silently
+            Err(error) => {
+                eprintln!("{error}");
+                ExitCode::from(error.exit_code())
+            }
+        }
+    }
+
+    /// Parses the command line and executes the requested utility, writing its output into out. As out isn't the standard output, it's never treated as a terminal (eg, --color=auto doesn't color it).
+    pub fn execute(out: &mut dyn Write) -> Result<Execution, CluErrors>{
+        Self::execute_from(env::args_os(), out)
+    }
+
+    /// Parses args as if they were the command line (the first one being the name of the program) and executes the requested utility, writing its output into out. It's useful to capture the output in a buffer, eg: Clu::execute_from(["clu", "grep", "pattern", "file.txt"], &mut Vec::new()). As in execute, out is never treated as a terminal. Wrong arguments aren't printed but returned as a UsageError, while the texts of --help and --version are written into out.
+    pub fn execute_from<I, T>(args: I, out: &mut dyn Write) -> Result<Execution, CluErrors>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>
+    {
+        Self::execute_into(args, out, false)
+    }
+
+    // Only run knows that out is the real standard output, and so whether it's a terminal
+    fn execute_into<I, T>(args: I, out: &mut dyn Write, terminal: bool) -> Result<Execution, CluErrors>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>
+    {
+        let clu = match Self::try_parse_from(find::expression::split_expression(args.into_iter().map(Into::into))){
+            Ok(clu) => clu,
+            // The texts of --help and --version aren't errors but the requested output
+            Err(error) if !error.use_stderr() => {
+                write!(out, "{}", error.render()).and_then(|_| out.flush()).map_err(CluErrors::output)?;
+                return Ok(Execution{ errors_occurred: false });
+            },
+            Err(error) => return Err(CluErrors::UsageError(error.render().to_string().trim_end().to_string()))
+        };
+        let execution = clu
+            .utility
+            .ok_or(CluErrors::MissingCommand)?
+            .execute(out, terminal);
+        out.flush().map_err(CluErrors::output)?; // Even if nothing is found, something may have been written (eg, the counts of 0)
+        execution
     }
 }
\ No newline at end of file
@@ -1,8 +1,6 @@
 use amazing_clu::Clu;
+use std::process::ExitCode;
 
-fn main(){
-    match Clu::run(){
-        Ok(output) => println!("{output}"),
-        Err(error) => println!("{error}")
-    }
+fn main() -> ExitCode{
+    Clu::run()
 }
\ No newline at end of file
@@ -0,0 +1,246 @@
+use std::{collections::VecDeque, num::NonZeroUsize, sync::{Mutex, MutexGuard, Condvar, mpsc, atomic::{AtomicBool, AtomicUsize, Ordering}}, thread::{self, Builder}};
+use crate::clu_errors::CluErrors;
+
+// How many outputs per worker may be waiting for the consumer before the workers are blocked
+const OUTPUTS_PER_WORKER: usize = 16;
+
+// Number of workers used if the user doesn't say otherwise: one per available core
+pub fn default_threads() -> usize{
+    thread::available_parallelism().map(NonZeroUsize::get).unwrap_or(1)
+}
+
+// The state shared among the workers of a pool
+struct Shared<T>{
+    // Each worker has its own queue for the tasks it pushes while working. The owner takes from the back, the other workers steal from the front
+    queues: Vec<Mutex<VecDeque<T>>>,
+    // Tasks taken or pushed but not finished yet. Once it reaches 0 and every initial task has been taken, there's nothing else to do
+    pending: AtomicUsize,
+    // Set once every initial task has been taken, so they aren't asked for anymore
+    exhausted: AtomicBool,
+    // Set once the consumer stops the pool, so the workers don't start any other task
+    cancelled: AtomicBool,
+    // Every change that may give work to an idle worker (or let it leave) is made while holding idle, and then notified through wake_up. As the idle workers check their state while holding it too, they cannot miss a notification
+    idle: Mutex<()>,
+    wake_up: Condvar
+}
+
+impl<T> Shared<T>{
+    fn idle(&self) -> MutexGuard<'_, ()>{
+        self.idle.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // Stops the pool, waking up the idle workers so they leave
+    fn cancel(&self){
+        let _idle = self.idle();
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.wake_up.notify_all();
+    }
+
+    // Whether any worker has pushed a task that nobody has taken yet
+    fn has_pushed_tasks(&self) -> bool{
+        self.queues.iter().any(|queue| queue.lock().is_ok_and(|queue| !queue.is_empty()))
+    }
+}
+
+// Handle given to the work function, used to push new tasks into the pool (eg, the subdirectories found while exploring a directory) and to send outputs before the task is finished
+pub struct Worker<'a, T, O>{
+    id: usize,
+    shared: &'a Shared<T>,
+    // The initial tasks, taken in order by a single worker at a time, so they're started in the same order they were given
+    injector: &'a Mutex<dyn Iterator<Item = T> + Send + 'a>,
+    outputs: mpsc::SyncSender<O>
+}
+
+impl<T, O> Worker<'_, T, O>{
+    pub fn push(&self, task: T){
+        // The task is counted before being visible, so the pool cannot be considered finished while it's pending
+        let _idle = self.shared.idle();
+        self.shared.pending.fetch_add(1, Ordering::SeqCst);
+        self.shared.queues[self.id].lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push_back(task);
+        self.shared.wake_up.notify_one();
+    }
+
+    // Sends an output to be consumed while the task goes on (eg, every line found in a file as soon as it's found), waiting if the consumer falls behind. It returns false once the pool has been stopped, so the task can be left unfinished
+    pub fn send(&self, output: O) -> bool{
+        !self.shared.cancelled.load(Ordering::SeqCst) && self.outputs.send(output).is_ok()
+    }
+
+    // Finds the next task: first from its own queue, then from the initial tasks and finally stealing from the other workers
+    fn next(&self) -> Option<T>{
+        let pop_front = |queue: &Mutex<VecDeque<T>>| queue.lock().ok().and_then(|mut queue| queue.pop_front());
+        if let Some(task) = self.shared.queues[self.id].lock().ok().and_then(|mut queue| queue.pop_back()){
+            return Some(task);
+        }
+        if let Some(task) = self.take_initial(){
+            return Some(task);
+        }
+        let workers = self.shared.queues.len();
+        (1..workers)
+            .map(|offset| (self.id + offset) % workers)
+            .find_map(|victim| pop_front(&self.shared.queues[victim]))
+    }
+
+    // Takes the next initial task, unless every one has been taken. The task is counted before the initial tasks are unlocked, so the pool cannot be considered finished in the meantime
+    fn take_initial(&self) -> Option<T>{
+        let mut injector = self.injector.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if self.shared.exhausted.load(Ordering::SeqCst){
+            return None;
+        }
+        let task = injector.next();
+        match task{
+            Some(_) => { self.shared.pending.fetch_add(1, Ordering::SeqCst); },
+            None => self.shared.exhausted.store(true, Ordering::SeqCst)
+        }
+        task
+    }
+
+    // Marks a task as finished, waking everybody up if it was the last one so they can leave
+    fn finish(&self){
+        let _idle = self.shared.idle();
+        if self.shared.pending.fetch_sub(1, Ordering::SeqCst) == 1{
+            self.shared.wake_up.notify_all();
+        }
+    }
+}
+
+// Marks the task being executed as finished once it's dropped, even if the work function panics. In that case the whole pool is stopped, as its output will never arrive, and the panic is reported as an error once the workers are joined
+struct Finisher<'a, T, O>{
+    worker: &'a Worker<'a, T, O>
+}
+
+impl<T, O> Drop for Finisher<'_, T, O>{
+    fn drop(&mut self){
+        if thread::panicking(){
+            self.worker.shared.cancel();
+        }
+        self.worker.finish();
+    }
+}
+
+// Keeps the tasks whose outputs are consumed in order (eg, the files whose results are written one after another) from running too far ahead of the next one to be consumed, so the outputs consumed out of their turn don't pile up. A task in the given position may only start while it's less than ahead positions after the next one
+pub struct Turns{
+    ahead: usize,
+    next: Mutex<usize>,
+    moved: Condvar
+}
+
+impl Turns{
+    pub fn new(ahead: usize) -> Turns{
+        Turns{ ahead: ahead.max(1), next: Mutex::new(0), moved: Condvar::new() }
+    }
+
+    fn next(&self) -> MutexGuard<'_, usize>{
+        self.next.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // Waits until the task in position may start. If the task panics while the returned Turn is alive, its output will never be consumed, so every other task is let start instead of waiting for it forever
+    pub fn wait(&self, position: usize) -> Turn<'_>{
+        let mut next = self.next();
+        while position.saturating_sub(*next) >= self.ahead{
+            next = self.moved.wait(next).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        Turn{ turns: self }
+    }
+
+    // Moves the next position to be consumed forward, letting the tasks waiting for it start
+    pub fn advance(&self, next: usize){
+        *self.next() = next;
+        self.moved.notify_all();
+    }
+
+    // Lets every task start, eg once the consumer stops, so no task is left waiting for a turn that won't come
+    pub fn release(&self){
+        self.advance(usize::MAX);
+    }
+}
+
+pub struct Turn<'a>{
+    turns: &'a Turns
+}
+
+impl Drop for Turn<'_>{
+    fn drop(&mut self){
+        if thread::panicking(){
+            self.turns.release();
+        }
+    }
+}
+
+// Executes work for every task using a bounded number of worker threads. The tasks are taken from their iterator while the workers go on, so they may be produced while the pool is running (eg, received from a channel). Note that the idle workers wait while one of them waits for the next task. The work function may push new tasks through its Worker, which are executed by the same worker unless another idle one steals them. Every output (the one returned by work, and those sent through its Worker before returning it) is sent through a bounded channel to consume, which is executed in the calling thread while the workers are still running, so the outputs are processed as soon as they're produced and the workers wait if the consumer falls behind. Once consume returns false (eg, the output cannot be written anymore), the rest of the outputs are discarded and the workers leave as soon as they finish their current task, without starting any other. If the work function panics, the pool is stopped as well and an UnexpectedError is returned.
+pub fn run<T, O, I, W, C>(threads: usize, tasks: I, work: W, mut consume: C) -> Result<(), CluErrors>
+where
+    T: Send,
+    O: Send,
+    I: IntoIterator<Item = T>,
+    I::IntoIter: Send,
+    W: Fn(T, &Worker<T, O>) -> O + Sync,
+    C: FnMut(O) -> bool
+{
+    let threads = threads.max(1);
+    let shared = Shared{
+        pending: AtomicUsize::new(0),
+        exhausted: AtomicBool::new(false),
+        cancelled: AtomicBool::new(false),
+        queues: (0..threads).map(|_| Mutex::new(VecDeque::new())).collect(),
+        idle: Mutex::new(()),
+        wake_up: Condvar::new()
+    };
+    let injector = Mutex::new(tasks.into_iter());
+    let injector: &Mutex<dyn Iterator<Item = T> + Send> = &injector;
+
+    thread::scope(|scope| -> Result<(), CluErrors>{
+        let (tx, rx) = mpsc::sync_channel(threads * OUTPUTS_PER_WORKER);
+        let mut handles = Vec::new();
+        let mut spawn_error = None;
+        for id in 0..threads{
+            let outputs = tx.clone();
+            let shared = &shared;
+            let work = &work;
+            let spawned = Builder::new().spawn_scoped(scope, move ||{
+                let worker = Worker{ id, shared, injector, outputs };
+                while !shared.cancelled.load(Ordering::SeqCst){
+                    match worker.next(){
+                        Some(task) => {
+                            let finisher = Finisher{ worker: &worker };
+                            let output = work(task, &worker);
+                            drop(finisher);
+                            if worker.outputs.send(output).is_err(){ // Nobody is listening anymore, so there's no point in keeping working
+                                break;
+                            }
+                        },
+                        None if shared.pending.load(Ordering::SeqCst) == 0 => break,
+                        None => {
+                            let idle = shared.idle();
+                            // Anything pushed, finished or cancelled since the last look has already been notified, so it has to be looked for again instead of waiting
+                            if !shared.cancelled.load(Ordering::SeqCst) && shared.pending.load(Ordering::SeqCst) > 0 && !shared.has_pushed_tasks(){
+                                drop(shared.wake_up.wait(idle).unwrap_or_else(|poisoned| poisoned.into_inner()));
+                            }
+                        }
+                    }
+                }
+            });
+            match spawned{
+                Ok(handle) => handles.push(handle),
+                Err(_) => { // The workers already spawned can still do the whole work, so we keep consuming their outputs to not leave them blocked
+                    spawn_error = Some(CluErrors::UnexpectedError);
+                    break;
+                }
+            }
+        }
+        drop(tx); // Once every worker leaves, the channel is closed and the consumer loop ends
+        for output in rx.iter(){
+            if !consume(output){
+                shared.cancel();
+                break;
+            }
+        }
+        drop(rx); // The workers find the channel closed, so they stop working
+        for handle in handles{
+            handle.join().map_err(|_err| CluErrors::UnexpectedError)?;
+        }
+        spawn_error.map_or(Ok(()), Err)
+    })
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
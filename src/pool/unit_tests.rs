@@ -0,0 +1,131 @@
+use std::{sync::{mpsc, atomic::{AtomicUsize, Ordering}}, thread, time::Duration};
+use crate::{pool, clu_errors::CluErrors};
+
+#[test]
+fn run_test(){
+    // Every initial task is executed once, no matter how many workers there are
+    for threads in [0, 1, 4]{
+        let mut outputs = Vec::new();
+        pool::run(threads, 0..100, |task: usize, _worker| task * 2, |output|{
+            outputs.push(output);
+            true
+        }).unwrap();
+        outputs.sort();
+        assert_eq!((0..100).map(|task| task * 2).collect::<Vec<usize>>(), outputs);
+    }
+
+    // The tasks pushed while working are executed as well. Each task n pushes the tasks 2n+1 and 2n+2 while they're lower than 1000, building a binary tree where every number appears once
+    let mut outputs = Vec::new();
+    pool::run(4, vec![0], |task: usize, worker| {
+        for child in [2 * task + 1, 2 * task + 2]{
+            if child < 1000{
+                worker.push(child);
+            }
+        }
+        task
+    }, |output|{
+        outputs.push(output);
+        true
+    }).unwrap();
+    outputs.sort();
+    assert_eq!((0..1000).collect::<Vec<usize>>(), outputs);
+
+    // A slow consumer blocks the workers instead of making the outputs pile up, but every output arrives anyway
+    let mut received = 0;
+    pool::run(2, 0..500, |task: usize, _worker| task, |_output| {
+        thread::sleep(Duration::from_micros(10));
+        received += 1;
+        true
+    }).unwrap();
+    assert_eq!(500, received);
+
+    // The consumer stops the pool, so no output arrives after it
+    let mut received = 0;
+    pool::run(4, 0..10000, |task: usize, _worker| task, |_output| {
+        received += 1;
+        received < 10
+    }).unwrap();
+    assert_eq!(10, received);
+
+    // A panicking task stops the pool instead of leaving the rest of the workers waiting for it, and it's reported as an error
+    for threads in [1, 4]{
+        let result = pool::run(threads, 0..1000, |task: usize, _worker| {
+            assert_ne!(500, task, "Unexpected task");
+            task
+        }, |_output| true);
+        assert_eq!(Err(CluErrors::UnexpectedError), result);
+    }
+
+    // The tasks may be produced while the pool is running: here the second task is only sent once the output of the first one has been consumed
+    let mut outputs = Vec::new();
+    let (tasks, received_tasks) = mpsc::channel();
+    let (consumed, first_consumed) = mpsc::channel();
+    tasks.send(0).unwrap();
+    let producer = thread::spawn(move ||{
+        let waited = first_consumed.recv_timeout(Duration::from_secs(10)).is_ok();
+        tasks.send(1).unwrap();
+        waited
+    });
+    pool::run(4, received_tasks, |task: usize, _worker| task, |output|{
+        outputs.push(output);
+        let _ = consumed.send(());
+        true
+    }).unwrap();
+    assert!(producer.join().unwrap());
+    assert_eq!(vec![0, 1], outputs);
+
+    // Nothing to do
+    let mut outputs: Vec<usize> = Vec::new();
+    pool::run(4, Vec::new(), |task: usize, _worker| task, |output|{
+        outputs.push(output);
+        true
+    }).unwrap();
+    assert!(outputs.is_empty());
+}
+
+#[test]
+fn turns_test(){
+    // Every task waits until its position is less than 2 positions after the next one
+    let turns = pool::Turns::new(2);
+    let started = AtomicUsize::new(0);
+    thread::scope(|scope|{
+        for position in 0..4{
+            let (turns, started) = (&turns, &started);
+            scope.spawn(move ||{
+                let _turn = turns.wait(position);
+                started.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        while started.load(Ordering::SeqCst) < 2{
+            thread::yield_now();
+        }
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(2, started.load(Ordering::SeqCst)); // The positions 2 and 3 are still waiting
+        turns.advance(1);
+        while started.load(Ordering::SeqCst) < 3{
+            thread::yield_now();
+        }
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(3, started.load(Ordering::SeqCst));
+        turns.release(); // Nobody waits after releasing
+    });
+    assert_eq!(4, started.load(Ordering::SeqCst));
+    let _turn = turns.wait(1000);
+
+    // A panicking task lets every other task start instead of leaving them waiting for its turn
+    let turns = pool::Turns::new(1);
+    thread::scope(|scope|{
+        let waiting = scope.spawn(|| { let _turn = turns.wait(5); });
+        let panicking = scope.spawn(|| {
+            let _turn = turns.wait(0);
+            panic!("Unexpected task");
+        });
+        assert!(panicking.join().is_err());
+        waiting.join().unwrap();
+    });
+}
+
+#[test]
+fn default_threads_test(){
+    assert!(pool::default_threads() >= 1);
+}
\ No newline at end of file
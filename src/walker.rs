@@ -0,0 +1,310 @@
+use clap::{Args, ValueEnum};
+use std::{io, sync::Arc, collections::BTreeMap, fs::{self, FileType, Metadata}, path::{Path, PathBuf}};
+use crate::{clu_errors::CluErrors, pool::{self, Worker}};
+
+pub mod glob;
+pub mod ignore;
+use glob::Glob;
+use ignore::Ignore;
+
+#[derive(Args, Clone, Copy)]
+pub struct Traversal{
+    /// Set which symbolic links are followed while going down in the directory tree. With never, they're never followed. With arguments, only the ones introduced as path are followed. With always, every symbolic link is followed, except those leading to a loop.
+    #[arg(long, value_enum, value_name = "POLICY", default_value_t = Symlinks::Arguments)]
+    pub symlinks: Symlinks,
+
+    /// Set how deep the directory tree is explored. The introduced paths are at depth 0, so --max-depth 1 only explores their contents, but not the contents of their subdirectories.
+    #[arg(long, value_name = "DEPTH")]
+    pub max_depth: Option<usize>,
+
+    /// By default, the items matched by the .gitignore, .ignore and .cluignore files found while going down in the directory tree are skipped, as well as those matched by the ignore files of the parent directories up to the root of the git repository. Set this flag on to explore everything. Note that the introduced paths are never skipped.
+    #[arg(long)]
+    pub no_ignore: bool,
+
+    /// Set this flag on to stay in the file system of every introduced path, without going down into the directories where other file systems are mounted.
+    #[arg(long)]
+    pub one_file_system: bool
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+pub enum Symlinks{
+    Never,
+    Arguments,
+    Always
+}
+
+// An item found while walking the directory tree. Its depth is 0 for the introduced paths, 1 for their contents and so on. Its file type is the one of the item a symbolic link points to if the link has been followed
+#[derive(Debug, Clone)]
+pub struct Entry{
+    pub path: String,
+    pub depth: usize,
+    pub file_type: FileType
+}
+
+// A piece of a path introduced by the user, once it's been split to be expanded
+enum Segment{
+    Literal(String),
+    Glob(Glob),
+    Recursive // The ** pattern, matching any number of directories
+}
+
+// An item pending to be visited. It's identified by its key: the position of each of its ancestors in their directory followed by its own position. Sorting by this key gives the traversal order back, no matter which worker visited the item. The canonical paths of its ancestors are kept to detect loops if symbolic links are followed, the rules of the ignore files found in them to skip the ignored items and the device of its parent to stay in the same file system if requested
+struct Task{
+    key: Vec<usize>,
+    path: String,
+    ancestors: Vec<PathBuf>,
+    ignore: Option<Arc<Ignore>>,
+    device: Option<u64>
+}
+
+// The result of visiting a task: the entry found, the error found if any and how many of its contents have been pushed into the pool
+struct Visited{
+    key: Vec<usize>,
+    entry: Option<Entry>,
+    error: Option<CluErrors>,
+    contents: usize
+}
+
+pub struct Walker{
+    pub hidden_items: bool,
+    pub traversal: Traversal,
+    pub threads: usize
+}
+
+impl Walker{
+
+    // Expands the paths introduced by the user into the items they refer to. A path naming an existing item is taken as it is, otherwise its glob patterns (*, ?, [...] and **) are expanded. By default, the hidden items (those starting with .) are only matched by patterns explicitly starting with a dot. The paths that don't refer to anything are reported into errors.
+    pub fn expand(&self, paths: &[String], errors: &mut Vec<CluErrors>) -> Vec<String>{
+        let mut expanded = Vec::new();
+        for path in paths{
+            let mut found = self.expand_path(path);
+            if found.is_empty(){
+                errors.push(CluErrors::InputError(format!("The introduced path: '{}' doesn't match any file or directory", path)));
+            }
+            expanded.append(&mut found);
+        }
+        expanded
+    }
+
+    fn expand_path(&self, path: &str) -> Vec<String>{
+        let path_object = Path::new(path);
+        if path.is_empty(){
+            return Vec::new();
+        }
+        if path_object.symlink_metadata().is_ok(){
+            return vec![path.to_string()];
+        }
+        if !Glob::has_wildcards(path){
+            return Vec::new();
+        }
+        // The components before the first one with wildcards are the directory where the expansion starts. Note that this directory may be empty if the first component already has wildcards (eg, *.txt), which means the current directory
+        let mut base = PathBuf::new();
+        let mut segments = Vec::new();
+        for component in path_object.components(){
+            let name = component.as_os_str().to_str().unwrap_or_default(); // Unwrap is OK as component comes from path which is already a valid String
+            if name == "**"{
+                segments.push(Segment::Recursive);
+            }
+            else if Glob::has_wildcards(name){
+                segments.push(Segment::Glob(Glob::new(name, false)));
+            }
+            else if segments.is_empty(){
+                base.push(component);
+            }
+            else{
+                segments.push(Segment::Literal(name.to_string()));
+            }
+        }
+        let mut found = Vec::new();
+        self.expand_segments(base, &segments, &mut found);
+        found
+            .into_iter()
+            .filter_map(|item| item.to_str().map(|item| item.to_string())) // Non readable items are ignored
+            .collect()
+    }
+
+    // Matches the segments against the contents of base, going down in the directory tree while there are segments left
+    fn expand_segments(&self, base: PathBuf, segments: &[Segment], found: &mut Vec<PathBuf>){
+        match segments.split_first(){
+            None => found.push(base),
+            Some((Segment::Literal(name), rest)) => {
+                let next = base.join(name);
+                if next.symlink_metadata().is_ok(){
+                    self.expand_segments(next, rest, found);
+                }
+            },
+            Some((Segment::Glob(glob), rest)) => {
+                for (name, _file_type) in self.read_names(&base, glob.matches_hidden()).unwrap_or_default(){
+                    let next = base.join(&name);
+                    if glob.is_match(&name) && (rest.is_empty() || next.is_dir()){
+                        self.expand_segments(next, rest, found);
+                    }
+                }
+            },
+            Some((Segment::Recursive, rest)) => {
+                if rest.is_empty(){ // A trailing ** matches everything below base
+                    self.expand_segments(base, &[Segment::Recursive, Segment::Glob(Glob::new("*", false))], found);
+                    return;
+                }
+                // ** may match no directory at all, or any directory below base. Symbolic links aren't followed here to avoid loops
+                self.expand_segments(base.clone(), rest, found);
+                for (name, file_type) in self.read_names(&base, false).unwrap_or_default(){
+                    if file_type.is_dir(){
+                        self.expand_segments(base.join(name), segments, found);
+                    }
+                }
+            }
+        }
+    }
+
+    // Reads the names of the items inside dir, sorted to always traverse the directories in the same order no matter the platform. Hidden items are skipped unless the user asked for them or they're explicitly requested. Items that cannot be read are ignored
+    fn read_names(&self, dir: &Path, hidden_requested: bool) -> io::Result<Vec<(String, FileType)>>{
+        let dir = if dir.as_os_str().is_empty(){ Path::new(".") } else{ dir };
+        let mut names: Vec<(String, FileType)> = dir.read_dir()?
+            .filter_map(|item| item.ok())
+            .filter_map(|item| Some((item.file_name().into_string().ok()?, item.file_type().ok()?)))
+            .filter(|(name, _file_type)| self.hidden_items || hidden_requested || !name.starts_with('.'))
+            .collect();
+        names.sort_by(|(name, _file_type), (other, _other_file_type)| name.cmp(other));
+        Ok(names)
+    }
+
+    // Walks down the directory tree from every path, once expanded, returning every entry found in the traversal order: every directory is followed by its contents. An item that cannot be read doesn't stop the walk: its error is pushed into errors and the rest of the tree is still explored.
+    pub fn walk(&self, paths: &[String], errors: &mut Vec<CluErrors>) -> Result<Vec<Entry>, CluErrors>{
+        let mut entries = Vec::new();
+        self.walk_pruning(paths, errors, |_entry| false, |entry|{
+            entries.push(entry);
+            true
+        })?;
+        Ok(entries)
+    }
+
+    // Walks down the directory tree like walk, without exploring the contents of the directories for which prune is true. The directories are explored concurrently by a pool of workers, and every entry is given to found as soon as the ones before it in the traversal order have been given, so the walk can be consumed while it goes on. Once found returns false, the walk stops. Note that prune is called by the workers, so it may be called for several entries at the same time
+    pub fn walk_pruning<P, F>(&self, paths: &[String], errors: &mut Vec<CluErrors>, prune: P, mut found: F) -> Result<(), CluErrors>
+    where
+        P: Fn(&Entry) -> bool + Sync,
+        F: FnMut(Entry) -> bool
+    {
+        let tasks: Vec<Task> = self.expand(paths, errors)
+            .into_iter()
+            .enumerate()
+            .map(|(position, path)| Task{ key: vec![position], path, ancestors: Vec::new(), ignore: None, device: None })
+            .collect();
+
+        // The items visited before their turn wait here. The key of the next item to be released is kept with the number of items of every level of it, so it can be moved forward without knowing the whole tree
+        let mut visited_items = BTreeMap::new();
+        let mut next = vec![0];
+        let mut sizes = vec![tasks.len()];
+        pool::run(self.threads, tasks, |task, worker| self.visit(task, worker, &prune), |visited|{
+            visited_items.insert(visited.key.clone(), visited);
+            while let Some(Visited{ entry, error, contents, .. }) = visited_items.remove(&next){
+                errors.extend(error);
+                if entry.is_some_and(|entry| !found(entry)){
+                    return false;
+                }
+                // The next item is its first content if it has any, otherwise the item after it in its directory or in the directory of its closest ancestor with items left
+                if contents > 0{
+                    next.push(0);
+                    sizes.push(contents);
+                }
+                else{
+                    while let Some(position) = next.last_mut(){
+                        *position += 1;
+                        if Some(&*position) != sizes.last(){
+                            break;
+                        }
+                        next.pop();
+                        sizes.pop();
+                    }
+                }
+            }
+            true
+        })
+    }
+
+    // Visits an item, pushing its contents into the pool if it's a directory to be explored
+    fn visit<P: Fn(&Entry) -> bool>(&self, task: Task, worker: &Worker<Task, Visited>, prune: &P) -> Visited{
+        let Task{ key, path, mut ancestors, ignore, device } = task;
+        let depth = key.len() - 1;
+        let metadata = match Path::new(&path).symlink_metadata(){
+            Ok(metadata) => metadata,
+            Err(_) => return Visited{ key, entry: None, error: Some(CluErrors::InputError(format!("{} cannot be read", path))), contents: 0 }
+        };
+        let follow = metadata.file_type().is_symlink() && match self.traversal.symlinks{
+            Symlinks::Never => false,
+            Symlinks::Arguments => depth == 0,
+            Symlinks::Always => true
+        };
+        let metadata = if follow{
+            fs::metadata(&path).unwrap_or(metadata) // A broken link is kept as a link
+        }
+        else{
+            metadata
+        };
+        let file_type = metadata.file_type();
+        let entry = Entry{ path: path.clone(), depth, file_type };
+
+        if !file_type.is_dir() || self.traversal.max_depth.is_some_and(|max_depth| depth >= max_depth) || prune(&entry){
+            return Visited{ key, entry: Some(entry), error: None, contents: 0 };
+        }
+        // A directory in another file system is found, but not explored
+        let item_device = self.traversal.one_file_system.then(|| Self::device(&metadata)).flatten();
+        if device.is_some() && item_device != device{
+            return Visited{ key, entry: Some(entry), error: None, contents: 0 };
+        }
+        if self.traversal.symlinks == Symlinks::Always{ // Loops can only happen if any symbolic link may be followed
+            let canonical = fs::canonicalize(&path).unwrap_or(PathBuf::from(&path));
+            if ancestors.contains(&canonical){
+                return Visited{ key, entry: Some(entry), error: Some(CluErrors::InputError(format!("{} leads to a file system loop, so it won't be explored", path))), contents: 0 };
+            }
+            ancestors.push(canonical);
+        }
+        let names = match self.read_names(Path::new(&path), false){
+            Ok(names) => names,
+            Err(_) => return Visited{ key, entry: Some(entry), error: Some(CluErrors::UnableToReadDirectory(path)), contents: 0 }
+        };
+        // The ignore files of this directory apply to its contents. The introduced paths also take the rules of their parent directories
+        let ignore = if self.traversal.no_ignore{
+            None
+        }
+        else if depth == 0{
+            Ignore::load(Ignore::load_parents(Path::new(&path)), Path::new(&path))
+        }
+        else{
+            Ignore::load(ignore, Path::new(&path))
+        };
+        // The positions of the contents only count the ones that are explored, so the walk knows which one comes after each of them
+        let mut contents = Vec::new();
+        for (name, file_type) in names{
+            let mut child = PathBuf::from(&path);
+            child.push(name);
+            if ignore.as_ref().is_some_and(|ignore| ignore.is_ignored(&child, file_type.is_dir())){
+                continue;
+            }
+            if let Some(child) = child.to_str(){ // Non readable items are ignored
+                let mut child_key = key.clone();
+                child_key.push(contents.len());
+                contents.push(Task{ key: child_key, path: child.to_string(), ancestors: ancestors.clone(), ignore: ignore.clone(), device: item_device });
+            }
+        }
+        // The contents are pushed into the pool, so any idle worker can go on with them. As the worker takes its own tasks from the back, they're pushed backwards to visit them in the traversal order, so they can be released as soon as they're visited
+        let pushed = contents.len();
+        contents.into_iter().rev().for_each(|child| worker.push(child));
+        Visited{ key, entry: Some(entry), error: None, contents: pushed }
+    }
+
+    // The device holding an item, which is only available in Unix
+    #[cfg(unix)]
+    fn device(metadata: &Metadata) -> Option<u64>{
+        use std::os::unix::fs::MetadataExt;
+        Some(metadata.dev())
+    }
+
+    #[cfg(not(unix))]
+    fn device(_metadata: &Metadata) -> Option<u64>{
+        None
+    }
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
@@ -0,0 +1,148 @@
+// A glob pattern matching a single path component (a file or directory name). It supports:
+// - * matching any sequence of characters.
+// - ? matching a single character.
+// - [...] matching a character in the class, which may contain ranges (a-z) and be negated with ! or ^ ([!a-z]).
+// - \ escaping the following character, so it's matched literally.
+// The ** pattern spanning several directories is handled by the walker, as it isn't about a single component.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glob{
+    tokens: Vec<Token>,
+    case_insensitive: bool
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token{
+    Literal(char),
+    AnyChar,
+    AnySequence,
+    Class{ negated: bool, ranges: Vec<(char, char)> }
+}
+
+impl Glob{
+    pub fn new(pattern: &str, case_insensitive: bool) -> Glob{
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut tokens = Vec::new();
+        let mut position = 0;
+        while position < chars.len(){
+            match chars[position]{
+                '*' => {
+                    if tokens.last() != Some(&Token::AnySequence){ // Several * in a row are the same as a single one
+                        tokens.push(Token::AnySequence);
+                    }
+                },
+                '?' => tokens.push(Token::AnyChar),
+                '\\' if position + 1 < chars.len() => {
+                    position += 1;
+                    tokens.push(Token::Literal(chars[position]));
+                },
+                '[' => match Self::parse_class(&chars[position + 1..]){
+                    Some((class, length)) => {
+                        tokens.push(class);
+                        position += length;
+                    },
+                    None => tokens.push(Token::Literal('[')) // As in the shell, an unclosed [ is just a character
+                },
+                character => tokens.push(Token::Literal(character))
+            }
+            position += 1;
+        }
+        Glob{ tokens, case_insensitive }
+    }
+
+    // Parses a class whose opening [ has already been consumed. Returns the class and the number of characters it takes, including the closing ]
+    fn parse_class(chars: &[char]) -> Option<(Token, usize)>{
+        let mut position = 0;
+        let negated = matches!(chars.first(), Some('!') | Some('^'));
+        if negated{
+            position += 1;
+        }
+        let mut ranges = Vec::new();
+        let mut first = true; // A ] just after the opening (or the negation) is part of the class
+        while position < chars.len(){
+            let start = match chars[position]{
+                ']' if !first => return Some((Token::Class{ negated, ranges }, position + 1)),
+                '\\' if position + 1 < chars.len() => {
+                    position += 1;
+                    chars[position]
+                },
+                character => character
+            };
+            first = false;
+            if position + 2 < chars.len() && chars[position + 1] == '-' && chars[position + 2] != ']'{
+                ranges.push((start, chars[position + 2]));
+                position += 3;
+            }
+            else{
+                ranges.push((start, start));
+                position += 1;
+            }
+        }
+        None
+    }
+
+    // Checks if the glob contains any wildcard, otherwise it's just a name
+    pub fn has_wildcards(pattern: &str) -> bool{
+        pattern.contains(['*', '?', '['])
+    }
+
+    // A name starting with . is hidden. Like in the shell, it can only be matched by a glob that explicitly starts with a .
+    pub fn matches_hidden(&self) -> bool{
+        self.tokens.first() == Some(&Token::Literal('.'))
+    }
+
+    pub fn is_match(&self, name: &str) -> bool{
+        let name: Vec<char> = name.chars().collect();
+        // Classic wildcard matching: advance through both sequences, remembering the last * to backtrack to it if the rest doesn't match
+        let (mut token, mut character) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+        while character < name.len(){
+            match self.tokens.get(token){
+                Some(Token::AnySequence) => {
+                    backtrack = Some((token, character));
+                    token += 1;
+                    continue;
+                },
+                Some(matcher) if self.matches_char(matcher, name[character]) => {
+                    token += 1;
+                    character += 1;
+                    continue;
+                },
+                _ => ()
+            }
+            match backtrack{
+                Some((star, consumed)) => { // Let the last * take one more character
+                    token = star + 1;
+                    character = consumed + 1;
+                    backtrack = Some((star, consumed + 1));
+                },
+                None => return false
+            }
+        }
+        self.tokens[token..].iter().all(|token| *token == Token::AnySequence)
+    }
+
+    fn matches_char(&self, token: &Token, character: char) -> bool{
+        let equals = |expected: char, character: char| if self.case_insensitive{
+            expected.to_lowercase().eq(character.to_lowercase())
+        }
+        else{
+            expected == character
+        };
+        match token{
+            Token::Literal(expected) => equals(*expected, character),
+            Token::AnyChar => true,
+            Token::AnySequence => false,
+            Token::Class{ negated, ranges } => {
+                let contained = ranges.iter().any(|(start, end)|
+                    (*start..=*end).contains(&character)
+                    ||
+                    (self.case_insensitive && character.to_lowercase().chain(character.to_uppercase()).any(|case| (*start..=*end).contains(&case)))
+                );
+                contained ^ negated
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
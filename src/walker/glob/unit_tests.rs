@@ -0,0 +1,53 @@
+use crate::walker::glob::Glob;
+
+#[test]
+fn is_match_test(){
+    // Names without wildcards must be exactly the same, so the dot isn't a regex wildcard and + isn't a quantifier
+    assert!(Glob::new("foo.txt", false).is_match("foo.txt"));
+    assert!(!Glob::new("foo.txt", false).is_match("fooXtxt"));
+    assert!(Glob::new("a+b.txt", false).is_match("a+b.txt"));
+    assert!(!Glob::new("a+b.txt", false).is_match("aab.txt"));
+
+    // * matches any sequence, even an empty one
+    assert!(Glob::new("*.txt", false).is_match("sample.txt"));
+    assert!(Glob::new("*.txt", false).is_match(".txt"));
+    assert!(!Glob::new("*.txt", false).is_match("sample.txt.bak"));
+    assert!(Glob::new("s*e*.t*", false).is_match("sample.txt"));
+    assert!(Glob::new("**", false).is_match("anything"));
+
+    // ? matches a single character
+    assert!(Glob::new("sample?.txt", false).is_match("sample2.txt"));
+    assert!(!Glob::new("sample?.txt", false).is_match("sample.txt"));
+
+    // Classes, ranges and their negation
+    assert!(Glob::new("sample[0-9].txt", false).is_match("sample2.txt"));
+    assert!(!Glob::new("sample[0-9].txt", false).is_match("samplea.txt"));
+    assert!(Glob::new("sample[!0-9].txt", false).is_match("samplea.txt"));
+    assert!(Glob::new("sample[^0-9].txt", false).is_match("samplea.txt"));
+    assert!(Glob::new("[]a]", false).is_match("]"));
+    assert!(Glob::new("[a-]", false).is_match("-"));
+
+    // Escaped characters and unclosed classes are literal
+    assert!(Glob::new("\\*.txt", false).is_match("*.txt"));
+    assert!(!Glob::new("\\*.txt", false).is_match("a.txt"));
+    assert!(Glob::new("[a-s+", false).is_match("[a-s+"));
+
+    // Case insensitive
+    assert!(!Glob::new("*.TXT", false).is_match("sample.txt"));
+    assert!(Glob::new("*.TXT", true).is_match("sample.txt"));
+    assert!(Glob::new("[A-Z]*", true).is_match("sample.txt"));
+}
+
+#[test]
+fn has_wildcards_test(){
+    assert!(Glob::has_wildcards("*.txt"));
+    assert!(Glob::has_wildcards("sample?.txt"));
+    assert!(Glob::has_wildcards("sample[0-9].txt"));
+    assert!(!Glob::has_wildcards("sample.txt"));
+}
+
+#[test]
+fn matches_hidden_test(){
+    assert!(Glob::new(".*", false).matches_hidden());
+    assert!(!Glob::new("*", false).matches_hidden());
+}
\ No newline at end of file
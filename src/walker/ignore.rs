@@ -0,0 +1,135 @@
+use std::{fs, sync::Arc, path::{Path, PathBuf}};
+use crate::walker::glob::Glob;
+
+// The ignore files honoured while going down in the directory tree. When several of them are in the same directory, the later ones take precedence
+pub const IGNORE_FILES: [&str; 3] = [".gitignore", ".ignore", ".cluignore"];
+
+// A line of an ignore file, following the .gitignore syntax
+#[derive(Debug)]
+struct Rule{
+    negated: bool, // Lines starting with ! include again what a previous rule ignored
+    dir_only: bool, // Lines ending with / only match directories
+    segments: Vec<Segment>
+}
+
+#[derive(Debug)]
+enum Segment{
+    Glob(Glob),
+    Recursive // The ** pattern, matching any number of directories
+}
+
+// The rules of the ignore files found in a directory, chained to those of its parent directories. The paths are matched relative to dir, with prefix in front of them when the rules come from a directory above the one where the walk started
+#[derive(Debug)]
+pub struct Ignore{
+    parent: Option<Arc<Ignore>>,
+    dir: PathBuf,
+    prefix: PathBuf,
+    rules: Vec<Rule>
+}
+
+impl Rule{
+    fn parse(line: &str) -> Option<Rule>{
+        // Trailing spaces are ignored unless they're escaped
+        let mut line = line.trim_end_matches('\r');
+        while line.ends_with(' ') && !line.ends_with("\\ "){
+            line = &line[..line.len() - 1];
+        }
+        if line.is_empty() || line.starts_with('#'){
+            return None;
+        }
+        let negated = line.starts_with('!');
+        if negated{
+            line = &line[1..];
+        }
+        let dir_only = line.ends_with('/');
+        let line = line.trim_end_matches('/');
+        if line.is_empty(){
+            return None;
+        }
+        // A pattern with a / at the beginning or in the middle is relative to the directory of the ignore file, otherwise it matches a name at any level
+        let anchored = line.contains('/');
+        let mut segments = Vec::new();
+        if !anchored{
+            segments.push(Segment::Recursive);
+        }
+        for name in line.trim_start_matches('/').split('/').filter(|name| !name.is_empty()){
+            if name == "**"{
+                segments.push(Segment::Recursive);
+            }
+            else{
+                segments.push(Segment::Glob(Glob::new(name, false)));
+            }
+        }
+        Some(Rule{ negated, dir_only, segments })
+    }
+
+    fn is_match(&self, names: &[&str], is_dir: bool) -> bool{
+        (is_dir || !self.dir_only) && Self::matches_segments(&self.segments, names)
+    }
+
+    fn matches_segments(segments: &[Segment], names: &[&str]) -> bool{
+        match segments.split_first(){
+            None => names.is_empty(),
+            // A trailing ** matches everything inside the directory, but not the directory itself
+            Some((Segment::Recursive, [])) => !names.is_empty(),
+            Some((Segment::Recursive, rest)) => (0..=names.len()).any(|skipped| Self::matches_segments(rest, &names[skipped..])),
+            Some((Segment::Glob(glob), rest)) => names.first().is_some_and(|name| glob.is_match(name)) && Self::matches_segments(rest, &names[1..])
+        }
+    }
+}
+
+impl Ignore{
+
+    // Loads the ignore files of dir, chained to parent. If dir doesn't have any rule, parent is returned as it is
+    pub fn load(parent: Option<Arc<Ignore>>, dir: &Path) -> Option<Arc<Ignore>>{
+        Self::load_with_prefix(parent, dir, dir, PathBuf::new())
+    }
+
+    // Loads the ignore files of the directories above the one where the walk starts, up to the root of the git repository containing it. Nothing is loaded if it isn't inside a git repository, as the ignore files of an unrelated directory up in the tree shouldn't hide anything
+    pub fn load_parents(dir: &Path) -> Option<Arc<Ignore>>{
+        let canonical = fs::canonicalize(dir).ok()?;
+        let parents: Vec<&Path> = canonical.ancestors().skip(1).collect();
+        if canonical.join(".git").exists(){
+            return None;
+        }
+        let repository = parents.iter().position(|parent| parent.join(".git").exists())?;
+        parents[..=repository]
+            .iter()
+            .rev()
+            .fold(None, |ignore, parent|{
+                let prefix = canonical.strip_prefix(parent).map(Path::to_path_buf).unwrap_or_default();
+                Self::load_with_prefix(ignore, parent, dir, prefix)
+            })
+    }
+
+    fn load_with_prefix(parent: Option<Arc<Ignore>>, source: &Path, dir: &Path, prefix: PathBuf) -> Option<Arc<Ignore>>{
+        let rules: Vec<Rule> = IGNORE_FILES
+            .iter()
+            .filter_map(|file| fs::read(source.join(file)).ok()) // Ignore files that cannot be read are just skipped
+            .flat_map(|contents| String::from_utf8_lossy(&contents).lines().filter_map(Rule::parse).collect::<Vec<Rule>>())
+            .collect();
+        if rules.is_empty(){
+            return parent;
+        }
+        Some(Arc::new(Ignore{ parent, dir: dir.to_path_buf(), prefix, rules }))
+    }
+
+    // Checks if path is ignored. The last rule matching it decides, and the rules of a directory take precedence over those of its parents
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool{
+        let mut ignore = Some(self);
+        while let Some(current) = ignore{
+            if let Ok(relative) = path.strip_prefix(&current.dir){
+                let relative = current.prefix.join(relative);
+                let names: Vec<&str> = relative.iter().filter_map(|name| name.to_str()).collect();
+                if let Some(rule) = current.rules.iter().rev().find(|rule| rule.is_match(&names, is_dir)){
+                    return !rule.negated;
+                }
+            }
+            ignore = current.parent.as_deref();
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod unit_tests;
\ No newline at end of file
@@ -0,0 +1,104 @@
+use std::{fs, path::Path};
+use crate::walker::ignore::{Ignore, Rule};
+
+// Checks if a rule made of line matches path
+fn is_match(line: &str, path: &str, is_dir: bool) -> bool{
+    let names: Vec<&str> = path.split('/').collect();
+    Rule::parse(line).unwrap().is_match(&names, is_dir)
+}
+
+#[test]
+fn parse_test(){
+    // Comments and blank lines aren't rules
+    assert!(Rule::parse("# comment").is_none());
+    assert!(Rule::parse("").is_none());
+    assert!(Rule::parse("   ").is_none());
+    assert!(Rule::parse("/").is_none());
+
+    // Negations and directories
+    let rule = Rule::parse("!build/").unwrap();
+    assert!(rule.negated);
+    assert!(rule.dir_only);
+    let rule = Rule::parse("\\!build").unwrap();
+    assert!(!rule.negated);
+    assert!(!rule.dir_only);
+    assert!(is_match("\\#file", "#file", false));
+    assert!(is_match("\\!file", "!file", false));
+}
+
+#[test]
+fn is_match_test(){
+    // A name matches at any level
+    assert!(is_match("*.log", "app.log", false));
+    assert!(is_match("*.log", "logs/2023/app.log", false));
+    assert!(!is_match("*.log", "app.log.txt", false));
+    assert!(is_match("target", "crate/target", true));
+    // Trailing spaces are ignored unless they're escaped
+    assert!(is_match("*.log  ", "app.log", false));
+    assert!(is_match("app\\ ", "app ", false));
+
+    // Only directories
+    assert!(is_match("target/", "target", true));
+    assert!(!is_match("target/", "target", false));
+
+    // A / at the beginning or in the middle anchors the pattern to the directory of the ignore file
+    assert!(is_match("/local.txt", "local.txt", false));
+    assert!(!is_match("/local.txt", "sub/local.txt", false));
+    assert!(is_match("doc/*.txt", "doc/notes.txt", false));
+    assert!(!is_match("doc/*.txt", "sub/doc/notes.txt", false));
+    assert!(!is_match("doc/*.txt", "doc/sub/notes.txt", false));
+
+    // ** matches any number of directories
+    assert!(is_match("**/notes.txt", "notes.txt", false));
+    assert!(is_match("**/notes.txt", "doc/sub/notes.txt", false));
+    assert!(is_match("doc/**/notes.txt", "doc/notes.txt", false));
+    assert!(is_match("doc/**/notes.txt", "doc/a/b/notes.txt", false));
+    assert!(is_match("doc/**", "doc/a/b/notes.txt", false));
+    assert!(!is_match("doc/**", "doc", true));
+}
+
+#[test]
+fn is_ignored_test(){
+    // Build a directory tree with ignore files at several levels
+    let mut root = std::env::temp_dir();
+    root.push(format!("amazing_clu_is_ignored_test_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("sub")).unwrap();
+    fs::write(root.join(".gitignore"), "*.log\nbuild/\n").unwrap();
+    fs::write(root.join(".ignore"), "!keep.log\n").unwrap();
+    fs::write(root.join("sub").join(".cluignore"), "!app.log\n/local.txt\n").unwrap();
+
+    let ignore = Ignore::load(None, &root).unwrap();
+    // Ignored by the .gitignore
+    assert!(ignore.is_ignored(&root.join("app.log"), false));
+    assert!(ignore.is_ignored(&root.join("build"), true));
+    assert!(!ignore.is_ignored(&root.join("build"), false));
+    assert!(!ignore.is_ignored(&root.join("app.txt"), false));
+    // The .ignore takes precedence over the .gitignore in the same directory
+    assert!(!ignore.is_ignored(&root.join("keep.log"), false));
+
+    // The rules of a directory take precedence over the ones of its parents
+    let sub_ignore = Ignore::load(Some(ignore.clone()), &root.join("sub")).unwrap();
+    assert!(!sub_ignore.is_ignored(&root.join("sub").join("app.log"), false));
+    assert!(sub_ignore.is_ignored(&root.join("sub").join("other.log"), false));
+    assert!(sub_ignore.is_ignored(&root.join("sub").join("local.txt"), false));
+    assert!(!ignore.is_ignored(&root.join("local.txt"), false));
+
+    // A directory without ignore files keeps the rules of its parent
+    fs::create_dir_all(root.join("sub").join("nested")).unwrap();
+    let nested_ignore = Ignore::load(Some(sub_ignore.clone()), &root.join("sub").join("nested")).unwrap();
+    assert!(Ignore::load(None, &root.join("sub").join("nested")).is_none());
+    assert!(!nested_ignore.is_ignored(&root.join("sub").join("nested").join("local.txt"), false));
+    assert!(nested_ignore.is_ignored(&root.join("sub").join("nested").join("other.log"), false));
+
+    // The ignore files of the parent directories are only loaded inside a git repository, and they're matched from their own directory
+    assert!(Ignore::load_parents(&root.join("sub")).is_none());
+    fs::create_dir_all(root.join(".git")).unwrap();
+    fs::write(root.join(".gitignore"), "/sub/local.txt\n").unwrap();
+    let parents_ignore = Ignore::load_parents(&root.join("sub")).unwrap();
+    assert!(parents_ignore.is_ignored(&root.join("sub").join("local.txt"), false));
+    assert!(!parents_ignore.is_ignored(&root.join("sub").join("app.log"), false));
+    assert!(!parents_ignore.is_ignored(Path::new("unrelated"), false));
+
+    fs::remove_dir_all(&root).unwrap();
+}
\ No newline at end of file
@@ -0,0 +1,147 @@
+use std::path::PathBuf;
+use crate::{walker::{Walker, Traversal, Symlinks}, clu_errors::CluErrors};
+
+// Builds the path to an item inside tests/base_files
+fn base_files(items: &[&str]) -> String{
+    let mut pathbuf = PathBuf::new();
+    pathbuf.push(".");
+    pathbuf.push("tests");
+    pathbuf.push("base_files");
+    items.iter().for_each(|item| pathbuf.push(item));
+    pathbuf.to_str().unwrap().to_string()
+}
+
+#[test]
+fn expand_test(){
+    let mut errors = Vec::new();
+    let without_hidden = Walker{ hidden_items: false, traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false }, threads: 1 };
+    let with_hidden = Walker{ hidden_items: true, traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false }, threads: 1 };
+
+    // Everything inside a directory, with and without hidden items
+    assert_eq!(vec![base_files(&["sample_text.txt"])], without_hidden.expand(&[base_files(&["*"])], &mut errors));
+    assert_eq!(vec![base_files(&[".hidden_folder"]), base_files(&[".hidden_text.txt"]), base_files(&["sample_text.txt"])], with_hidden.expand(&[base_files(&["*"])], &mut errors));
+
+    // Hidden items explicitly requested
+    assert_eq!(vec![base_files(&[".hidden_folder"]), base_files(&[".hidden_text.txt"])], without_hidden.expand(&[base_files(&[".*"])], &mut errors));
+
+    // Existing items are taken as they are, even if they're hidden
+    assert_eq!(vec![base_files(&[".hidden_text.txt"])], without_hidden.expand(&[base_files(&[".hidden_text.txt"])], &mut errors));
+    assert_eq!(vec![base_files(&[])], without_hidden.expand(&[base_files(&[])], &mut errors));
+
+    // Wildcards in the middle of the path and ** to go down in the tree
+    assert_eq!(vec![base_files(&[".hidden_folder", "sample_text2.txt"])], without_hidden.expand(&[base_files(&[".hidden_*", "*.txt"])], &mut errors));
+    assert_eq!(vec![base_files(&["sample_text.txt"]), base_files(&[".hidden_folder", "sample_text2.txt"])], with_hidden.expand(&[base_files(&["**", "sample_text*.txt"])], &mut errors));
+    assert_eq!(vec![base_files(&["sample_text.txt"])], without_hidden.expand(&[base_files(&["**", "sample_text*.txt"])], &mut errors));
+    assert!(errors.is_empty());
+
+    // Paths without parent
+    assert_eq!(vec!["/".to_string()], without_hidden.expand(&["/".to_string()], &mut errors));
+    assert_eq!(vec![".".to_string()], without_hidden.expand(&[".".to_string()], &mut errors));
+    assert!(errors.is_empty());
+
+    // Names that aren't a regex anymore: the dot is a literal
+    assert!(without_hidden.expand(&[base_files(&["sample_textXtxt"])], &mut errors).is_empty());
+    assert_eq!(vec![CluErrors::InputError(format!("The introduced path: '{}' doesn't match any file or directory", base_files(&["sample_textXtxt"])))], errors);
+
+    // Empty path
+    let mut errors = Vec::new();
+    assert!(without_hidden.expand(&["".to_string()], &mut errors).is_empty());
+    assert_eq!(vec![CluErrors::InputError("The introduced path: '' doesn't match any file or directory".to_string())], errors);
+}
+
+#[test]
+fn walk_test(){
+    let mut errors = Vec::new();
+    let without_hidden = Walker{ hidden_items: false, traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false }, threads: 4 };
+    let with_hidden = Walker{ hidden_items: true, traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false }, threads: 4 };
+    let paths = |entries: Vec<crate::walker::Entry>| entries.into_iter().map(|entry| entry.path).collect::<Vec<String>>();
+    let pruned = |walker: &Walker, errors: &mut Vec<CluErrors>, prune: fn(&crate::walker::Entry) -> bool|{
+        let mut found = Vec::new();
+        walker.walk_pruning(&[base_files(&[])], errors, prune, |entry|{
+            found.push(entry.path);
+            true
+        }).unwrap();
+        found
+    };
+
+    // Base case test, the path only contains a file
+    assert_eq!(vec![base_files(&["sample_text.txt"])], paths(without_hidden.walk(&[base_files(&["sample_text.txt"])], &mut errors).unwrap()));
+
+    // Recursive case test, finding everything in the directory tree in traversal order: every directory is followed by its contents
+    assert_eq!(vec![base_files(&[]), base_files(&["sample_text.txt"])], paths(without_hidden.walk(&[base_files(&[])], &mut errors).unwrap()));
+    assert_eq!(
+        vec![base_files(&[]), base_files(&[".hidden_folder"]), base_files(&[".hidden_folder", "sample_text2.txt"]), base_files(&[".hidden_text.txt"]), base_files(&["sample_text.txt"])],
+        paths(with_hidden.walk(&[base_files(&[])], &mut errors).unwrap())
+    );
+
+    // Several paths keep their order
+    assert_eq!(
+        vec![base_files(&["sample_text.txt"]), base_files(&[".hidden_folder"]), base_files(&[".hidden_folder", "sample_text2.txt"])],
+        paths(with_hidden.walk(&[base_files(&["sample_text.txt"]), base_files(&[".hidden_folder"])], &mut errors).unwrap())
+    );
+
+    // Depth limit
+    let limited = Walker{ hidden_items: true, traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: Some(1), no_ignore: false, one_file_system: false }, threads: 4 };
+    assert_eq!(
+        vec![base_files(&[]), base_files(&[".hidden_folder"]), base_files(&[".hidden_text.txt"]), base_files(&["sample_text.txt"])],
+        paths(limited.walk(&[base_files(&[])], &mut errors).unwrap())
+    );
+    let limited = Walker{ hidden_items: true, traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: Some(0), no_ignore: false, one_file_system: false }, threads: 4 };
+    assert_eq!(vec![base_files(&[])], paths(limited.walk(&[base_files(&[])], &mut errors).unwrap()));
+
+    // Pruned directories are found, but not explored
+    assert_eq!(
+        vec![base_files(&[]), base_files(&[".hidden_folder"]), base_files(&[".hidden_text.txt"]), base_files(&["sample_text.txt"])],
+        pruned(&with_hidden, &mut errors, |entry| entry.path.ends_with(".hidden_folder"))
+    );
+
+    // The entries are given one by one in the traversal order, and the walk stops once they aren't wanted anymore
+    let mut given = Vec::new();
+    with_hidden.walk_pruning(&[base_files(&[])], &mut errors, |_entry| false, |entry|{
+        given.push(entry.path);
+        given.len() < 2
+    }).unwrap();
+    assert_eq!(vec![base_files(&[]), base_files(&[".hidden_folder"])], given);
+
+    // Staying in the same file system doesn't change anything here
+    let same_file_system = Walker{ hidden_items: true, traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: true }, threads: 4 };
+    assert_eq!(paths(with_hidden.walk(&[base_files(&[])], &mut errors).unwrap()), paths(same_file_system.walk(&[base_files(&[])], &mut errors).unwrap()));
+    assert!(errors.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn walk_symlinks_test(){
+    use std::{fs, slice, os::unix::fs::symlink};
+    // Build a directory containing a file and a link to itself, which is a loop
+    let mut root = std::env::temp_dir();
+    root.push(format!("amazing_clu_walk_symlinks_test_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("file.txt"), "").unwrap();
+    symlink(&root, root.join("loop")).unwrap();
+    let root_path = root.to_str().unwrap().to_string();
+    let paths = |entries: Vec<crate::walker::Entry>| entries.into_iter().map(|entry| entry.path).collect::<Vec<String>>();
+
+    // The link isn't followed, so it's just an entry
+    let mut errors = Vec::new();
+    let walker = Walker{ hidden_items: false, traversal: Traversal{ symlinks: Symlinks::Arguments, max_depth: None, no_ignore: false, one_file_system: false }, threads: 2 };
+    let entries = walker.walk(slice::from_ref(&root_path), &mut errors).unwrap();
+    assert!(entries[2].file_type.is_symlink());
+    assert_eq!(vec![root_path.clone(), format!("{}/file.txt", root_path), format!("{}/loop", root_path)], paths(entries));
+    assert!(errors.is_empty());
+
+    // The link is introduced as path, so it's followed with the arguments policy but not with the never one
+    let link = format!("{}/loop", root_path);
+    assert_eq!(vec![link.clone(), format!("{}/file.txt", link), format!("{}/loop", link)], paths(walker.walk(slice::from_ref(&link), &mut errors).unwrap()));
+    let walker = Walker{ hidden_items: false, traversal: Traversal{ symlinks: Symlinks::Never, max_depth: None, no_ignore: false, one_file_system: false }, threads: 2 };
+    assert_eq!(vec![link.clone()], paths(walker.walk(slice::from_ref(&link), &mut errors).unwrap()));
+    assert!(errors.is_empty());
+
+    // Following every link, the loop is detected instead of going down forever
+    let walker = Walker{ hidden_items: false, traversal: Traversal{ symlinks: Symlinks::Always, max_depth: None, no_ignore: false, one_file_system: false }, threads: 2 };
+    assert_eq!(vec![root_path.clone(), format!("{}/file.txt", root_path), format!("{}/loop", root_path)], paths(walker.walk(slice::from_ref(&root_path), &mut errors).unwrap()));
+    assert_eq!(vec![CluErrors::InputError(format!("{}/loop leads to a file system loop, so it won't be explored", root_path))], errors);
+
+    fs::remove_dir_all(&root).unwrap();
+}
\ No newline at end of file
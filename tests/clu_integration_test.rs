@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+use amazing_clu::{Clu, CluErrors, EXIT_ERROR};
+
+#[test]
+// The utilities can be executed as a library, writing their output into any writer instead of the standard output. Let's test:
+// 1. The output of grep and find captured in a buffer.
+// 2. An empty research, which doesn't write anything.
+// 3. The buffer is never a terminal, so --color=auto doesn't color it, even if the tests are run in a terminal.
+// 4. Wrong arguments are returned as an error instead of exiting, and --version is written into the buffer.
+fn clu_execute_from_test(){
+    // Path to the test folder
+    let mut path = PathBuf::new();
+    path.push(".");
+    path.push("tests");
+    path.push("find_files");
+    let mut file = path.clone();
+    file.push("subfolder1");
+    file.push("found_file2.txt");
+    let mut sample_text = PathBuf::new();
+    sample_text.push(".");
+    sample_text.push("tests");
+    sample_text.push("grep_files");
+    sample_text.push("sample_text.txt");
+
+    // Grep captured in a buffer
+    let mut buffer = Vec::new();
+    let execution = Clu::execute_from(["clu", "grep", "grep", sample_text.to_str().unwrap(), "-l"], &mut buffer).unwrap();
+    assert!(!execution.errors_occurred);
+    assert_eq!(format!("{}\n", sample_text.to_str().unwrap()), String::from_utf8(buffer).unwrap());
+
+    // Find captured in a buffer
+    let mut buffer = Vec::new();
+    Clu::execute_from(["clu", "find", path.to_str().unwrap(), "-name", "found_file2.txt"], &mut buffer).unwrap();
+    assert_eq!(format!("{}\n", file.to_str().unwrap()), String::from_utf8(buffer).unwrap());
+
+    // Empty research
+    let mut buffer = Vec::new();
+    assert_eq!(CluErrors::NotFoundError, Clu::execute_from(["clu", "find", path.to_str().unwrap(), "-name", "unexistent"], &mut buffer).unwrap_err());
+    assert!(buffer.is_empty());
+
+    // Not colored by default
+    let mut buffer = Vec::new();
+    Clu::execute_from(["clu", "grep", "--color=auto", "grep", sample_text.to_str().unwrap(), "-l"], &mut buffer).unwrap();
+    assert_eq!(format!("{}\n", sample_text.to_str().unwrap()), String::from_utf8(buffer).unwrap());
+
+    // Wrong arguments
+    let mut buffer = Vec::new();
+    let error = Clu::execute_from(["clu", "grep", "--bogus"], &mut buffer).unwrap_err();
+    assert!(matches!(&error, CluErrors::UsageError(message) if message.contains("--bogus")));
+    assert_eq!(EXIT_ERROR, error.exit_code());
+    assert!(buffer.is_empty());
+
+    // Version
+    let mut buffer = Vec::new();
+    assert!(!Clu::execute_from(["clu", "--version"], &mut buffer).unwrap().errors_occurred);
+    assert_eq!("amazing_clu 0.1.0\n", String::from_utf8(buffer).unwrap());
+}
\ No newline at end of file
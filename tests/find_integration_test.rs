@@ -63,8 +63,9 @@ fn find_execute_test(){
     // Empty research
     let mut cmd = Command::new(binary.clone());
     cmd.arg("find").arg(from.to_str().unwrap()).arg("-e").arg("*.js"); 
-    cmd.assert().success();
-    cmd.assert().stdout("The request didn't produce any output.\n");
+    cmd.assert().code(1); // An empty research exits with 1, silently as in grep
+    cmd.assert().stdout("");
+    cmd.assert().stderr("");
 
     // Research in several directories 
     let mut from2 = PathBuf::new();
@@ -78,4 +79,335 @@ fn find_execute_test(){
     assert!(stdout.contains("found_file1.txt")); // This is inside tests/find_files
     assert!(stdout.contains("sample_text.txt")); // This is inside tests/base_files
 
+
+    // The number of threads doesn't change the result, as the traversal order is kept
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-H").arg("-j").arg("1");
+    cmd.assert().success();
+    unsafe {stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout);}
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-H").arg("-j").arg("8");
+    cmd.assert().success().stdout(stdout);
+
+}
+
+
+#[test]
+// The items matched by the ignore files are skipped while going down in the directory tree. Let's test:
+// 1. The .ignore and the nested .cluignore are honoured, including their negations.
+// 2. The introduced paths are never skipped.
+// 3. --no-ignore explores everything.
+fn find_ignore_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test folder
+    let mut from = PathBuf::new();
+    from.push(".");
+    from.push("tests");
+    from.push("ignore_files");
+
+    // This variable will hold the output
+    let mut stdout;
+
+    // The ignored files aren't found
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-type").arg("f");
+    cmd.assert().success();
+    unsafe{ stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout);} // The output is for sure a valid String
+    let mut expected = Vec::new();
+    for item in [vec!["keep.log"], vec!["sample.txt"], vec!["sub", "app.log"], vec!["sub", "nested", "local.txt"], vec!["sub", "other.txt"]]{
+        let mut path = from.clone();
+        item.iter().for_each(|name| path.push(name));
+        expected.push(path.to_str().unwrap().to_string());
+    }
+    assert_eq!(expected.join("\n") + "\n", stdout);
+
+    // An ignored directory is explored if it's introduced as path
+    let mut build = from.clone();
+    build.push("build");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(build.to_str().unwrap());
+    cmd.assert().success();
+    unsafe{ stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout);}
+    assert!(stdout.contains("output.txt"));
+
+    // Everything is found without the ignore files
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("--no-ignore");
+    cmd.assert().success();
+    unsafe{ stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout);}
+    assert!(stdout.contains("app.log"));
+    assert!(stdout.contains("output.txt"));
+    assert!(stdout.contains("sub/local.txt"));
+}
+
+
+
+#[test]
+// The items found can be filtered with an expression made of tests and operators, written like in GNU find. Let's test:
+// 1. Directories and files are found unless -type says otherwise.
+// 2. Tests combined with operators and parentheses, mixed with the flags of find.
+// 3. Errors in the expression are reported before searching, exiting with 2.
+fn find_expression_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test folder
+    let mut from = PathBuf::new();
+    from.push(".");
+    from.push("tests");
+    from.push("find_files");
+    let path = |items: &[&str]|{
+        let mut path = from.clone();
+        items.iter().for_each(|item| path.push(item));
+        path.to_str().unwrap().to_string()
+    };
+
+    // Only the directories
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-type").arg("d");
+    cmd.assert().success().stdout(format!("{}\n{}\n{}\n", path(&[]), path(&["subfolder1"]), path(&["subfolder1", "subfolder2"])));
+
+    // The HTML file or the files in the second subfolder, with a flag of find in the middle of the expression
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-name").arg("*.html").arg("-H").arg("-o").arg("(").arg("-path").arg("*subfolder2*").arg("-type").arg("f").arg(")");
+    cmd.assert().success().stdout(format!("{}\n{}\n", path(&["found_html.html"]), path(&["subfolder1", "subfolder2", "found_file3.txt"])));
+
+    // The hidden files that aren't text files
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-H").arg("-type").arg("f").arg("!").arg("-name").arg("[!.]*").arg("-not").arg("-iname").arg("*.TXT");
+    cmd.assert().code(1);
+
+    // Errors in the expression
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("(").arg("-name").arg("*.html");
+    cmd.assert().code(2).stdout("").stderr("Input error. Missing ')' in the find expression.\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-type").arg("x");
+    cmd.assert().code(2).stdout("").stderr("Input error. Invalid argument 'x' to -type.\n");
+}
+
+
+
+#[test]
+// The items found can be handled with actions instead of being printed one per line. Let's test:
+// 1. -print0 and -printf.
+// 2. -exec running a command for every item or for batches of items.
+// 3. -delete, which must be confirmed.
+fn find_actions_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test folder
+    let mut from = PathBuf::new();
+    from.push(".");
+    from.push("tests");
+    from.push("find_files");
+    let path = |items: &[&str]|{
+        let mut path = from.clone();
+        items.iter().for_each(|item| path.push(item));
+        path.to_str().unwrap().to_string()
+    };
+
+    // Paths separated by the null character
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-type").arg("d").arg("-print0");
+    cmd.assert().success().stdout(format!("{}\0{}\0{}\0", path(&[]), path(&["subfolder1"]), path(&["subfolder1", "subfolder2"])));
+
+    // Formatted output
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-name").arg("*.html").arg("-printf").arg("%f %s %d %y\\n");
+    cmd.assert().success().stdout("found_html.html 0 1 f\n");
+
+    // A command for every item and a command for all of them
+    if cfg!(unix){
+        let mut cmd = Command::new(binary.clone());
+        cmd.arg("find").arg(from.to_str().unwrap()).arg("-name").arg("*.html").arg("-exec").arg("echo").arg("found:{}").arg(";");
+        cmd.assert().success().stdout(format!("found:{}\n", path(&["found_html.html"])));
+        let mut cmd = Command::new(binary.clone());
+        cmd.arg("find").arg(from.to_str().unwrap()).arg("-type").arg("d").arg("-exec").arg("echo").arg("{}").arg("+");
+        cmd.assert().success().stdout(format!("{} {} {}\n", path(&[]), path(&["subfolder1"]), path(&["subfolder1", "subfolder2"])));
+    }
+
+    // Deleting isn't allowed without confirmation, and the standard input isn't a terminal here
+    let mut root = env::temp_dir();
+    root.push(format!("amazing_clu_find_actions_test_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("dir")).unwrap();
+    std::fs::write(root.join("dir").join("file.txt"), "").unwrap();
+    std::fs::write(root.join("kept.rs"), "").unwrap();
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(root.to_str().unwrap()).arg("-name").arg("*.txt").arg("-delete");
+    cmd.assert().code(2).stderr("Input error. -delete removes the items for good, so it must be confirmed. Add --yes to delete them without being asked.\n");
+    assert!(root.join("dir").join("file.txt").exists());
+
+    // The directory is deleted after its contents
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(root.join("dir").to_str().unwrap()).arg("-delete").arg("--yes");
+    cmd.assert().success().stdout("");
+    assert!(!root.join("dir").exists());
+    assert!(root.join("kept.rs").exists());
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+
+
+#[test]
+// The global options of the expression change how the directory tree is walked. Let's test:
+// 1. -mindepth and -maxdepth, also combined with --max-depth.
+// 2. -prune skipping the contents of a directory.
+// 3. -depth and -xdev.
+fn find_depth_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test folder
+    let mut from = PathBuf::new();
+    from.push(".");
+    from.push("tests");
+    from.push("find_files");
+    let path = |items: &[&str]|{
+        let mut path = from.clone();
+        items.iter().for_each(|item| path.push(item));
+        path.to_str().unwrap().to_string()
+    };
+
+    // Only the contents of the first subfolder, with -mindepth and -maxdepth anywhere in the expression
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-mindepth").arg("2").arg("-name").arg("*").arg("-maxdepth").arg("2");
+    cmd.assert().success().stdout(format!("{}\n{}\n", path(&["subfolder1", "found_file2.txt"]), path(&["subfolder1", "subfolder2"])));
+
+    // The smallest of --max-depth and -maxdepth is taken
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("--max-depth").arg("1").arg("-maxdepth").arg("3").arg("-type").arg("d");
+    cmd.assert().success().stdout(format!("{}\n{}\n", path(&[]), path(&["subfolder1"])));
+
+    // The second subfolder is found, but not explored
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-name").arg("subfolder2").arg("-prune").arg("-o").arg("-type").arg("f").arg("-print");
+    cmd.assert().success().stdout(format!("{}\n{}\n{}\n", path(&["found_file1.txt"]), path(&["found_html.html"]), path(&["subfolder1", "found_file2.txt"])));
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-name").arg("subfolder2").arg("-prune");
+    cmd.assert().success().stdout(format!("{}\n", path(&["subfolder1", "subfolder2"])));
+
+    // The contents of every directory go before the directory itself, which makes -prune useless
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-depth").arg("-path").arg("*subfolder*").arg("-o").arg("-name").arg("subfolder2").arg("-prune");
+    cmd.assert().success().stdout(format!(
+        "{}\n{}\n{}\n{}\n",
+        path(&["subfolder1", "found_file2.txt"]),
+        path(&["subfolder1", "subfolder2", "found_file3.txt"]),
+        path(&["subfolder1", "subfolder2"]),
+        path(&["subfolder1"])
+    ));
+
+    // Staying in the same file system doesn't change anything here
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-xdev").arg("-type").arg("d");
+    cmd.assert().success().stdout(format!("{}\n{}\n{}\n", path(&[]), path(&["subfolder1"]), path(&["subfolder1", "subfolder2"])));
+
+    // Invalid depth
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from.to_str().unwrap()).arg("-maxdepth").arg("x");
+    cmd.assert().code(2).stdout("").stderr("Input error. Invalid argument 'x' to -maxdepth.\n");
+}
+
+
+#[test]
+// The items found can be written as JSON Lines to be read by other programs. Let's test:
+// 1. -print writes an object for every item with its path, depth, type and size.
+// 2. The rest of the actions keep their own output.
+fn find_json_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test folder
+    let mut from = PathBuf::new();
+    from.push(".");
+    from.push("tests");
+    from.push("find_files");
+    from.push("subfolder1");
+    let path = from.to_str().unwrap();
+
+    // The modification time depends on the checkout, so it's checked apart
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(path).arg("--json").arg("-maxdepth").arg("1").arg("-type").arg("f");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(1, lines.len());
+    assert!(lines[0].starts_with(&format!(r#"{{"type":"entry","data":{{"path":{{"text":"{}/found_file2.txt"}},"depth":1,"file_type":"file","size":0,"mtime":{{"secs":"#, path)));
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(path).arg("--json").arg("-maxdepth").arg("1").arg("-type").arg("f").arg("-printf").arg("%f\\n");
+    cmd.assert().success().stdout("found_file2.txt\n");
+}
+
+
+#[test]
+// The case options of grep are shared by find. Let's test:
+// 1. -S ignores the case of the regex only if it's lowercase.
+// 2. --no-ignore-case overrides -i.
+// 3. --case-folding also applies to -iregex.
+// 4. -regex follows -i and -S.
+fn find_case_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test folder
+    let mut from = PathBuf::new();
+    from.push(".");
+    from.push("tests");
+    from.push("find_files");
+    let mut found = from.clone();
+    found.push("found_file1.txt");
+    let from = from.to_str().unwrap();
+    let found = format!("{}\n", found.to_str().unwrap());
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from).arg("-S").arg("-e").arg("found_file1");
+    cmd.assert().success().stdout(found.clone());
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from).arg("-S").arg("-e").arg("FOUND_file1");
+    cmd.assert().code(1).stdout("");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from).arg("-i").arg("-e").arg("FOUND_FILE1");
+    cmd.assert().success().stdout(found.clone());
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from).arg("-i").arg("--no-ignore-case").arg("-e").arg("FOUND_FILE1");
+    cmd.assert().code(1).stdout("");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from).arg("--case-folding").arg("ascii").arg("-iregex").arg(r".*FOUND_FILE1\.TXT");
+    cmd.assert().success().stdout(found.clone());
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from).arg("-i").arg("-regex").arg(r".*FOUND_FILE1\.TXT");
+    cmd.assert().success().stdout(found.clone());
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from).arg("-S").arg("-regex").arg(r".*FOUND_FILE1\.TXT");
+    cmd.assert().code(1).stdout("");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("find").arg(from).arg("-S").arg("-regex").arg(r".*found_file1\.txt");
+    cmd.assert().success().stdout(found);
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use std::{path::PathBuf, env};
+use std::{path::PathBuf, env, io::{BufRead, BufReader, Read, Write}, process::Stdio, sync::mpsc, thread, time::Duration};
 use assert_cmd::Command;
 
 #[test] 
@@ -6,8 +6,8 @@ use assert_cmd::Command;
 // This function calls the other Grep's functions and propagates their errors. Let's test:
 // 1. A successful case if dereference recursive is not set on.
 // 2. A successful case if dereference recursive is set on. (1 and 2 also serves as tests for globbing cause it's executed here)
-// 3. Invalid command combination error
-// 4. Empty research error.
+// 3. Invalid command combination error, exiting with 2.
+// 4. Empty research error, exiting with 1.
 // The other errors are inherited from the Grep's functions and are tested in their corresponding tests.
 fn grep_execute_test(){
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
@@ -28,41 +28,899 @@ fn grep_execute_test(){
     // Non dereference recursive test
     path.push("*.txt"); // Seatch for .txt files
     let mut cmd = Command::new(binary.clone());
-    cmd.arg("grep").arg("grep").arg(path.to_str().unwrap()).arg("-n");
+    cmd.arg("grep").arg("grep").arg(path.to_str().unwrap()).arg("-n").arg("-H");
     cmd.assert().success(); // Ensure the command were well run
     unsafe {stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout)} //The output is for sure a valid String
     // The output contains the name of the file and the matched pattern. It does not contain the hidden text
-    assert!(stdout.contains("sample_text.txt"));
-    assert!(stdout.contains("3:I'm grep"));
+    assert!(stdout.contains("sample_text.txt:3:I'm grep"));
     assert!(!stdout.contains(".hidden_text.txt"));
 
     // Dereference recursive test
     path.pop(); // Search in the whole grep_files directory
     let mut cmd = Command::new(binary.clone());
-    cmd.arg("grep").arg("grep").arg(path.to_str().unwrap()).arg("-R").arg("-H").arg("-C").arg("1").arg("-n");
+    cmd.arg("grep").arg("grep").arg(path.to_str().unwrap()).arg("-R").arg("--hidden-items").arg("-C").arg("1").arg("-n");
     cmd.assert().success();
     // The output contains the files identifiers where the search has been successful, we cannot ensure the order in which these results are obtained due to the concurrency of our grep, then we can just ensure that we've found what we're looking for.
     unsafe { stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout)}
+    // Every line is prefixed with its file, and the groups of lines of different files are separated
+    let file = |name: &str|{
+        let mut file = path.clone();
+        file.push(name);
+        file.to_str().unwrap().to_string()
+    };
     // sample_text.txt found!
-    assert!(stdout.contains("sample_text.txt"));
-    assert!(stdout.contains("2-How are you? Who are you?\n3:I'm grep\n4-Nice to meet you"));
+    assert!(stdout.contains(&format!("{0}-2-How are you? Who are you?\n{0}:3:I'm grep\n{0}-4-Nice to meet you", file("sample_text.txt"))));
     // .hidden_text.txt found!
-    assert!(stdout.contains(".hidden_text.txt"));
-    assert!(stdout.contains("1:I'm a hidden file created to test grep!"));
+    assert!(stdout.contains(&format!("--\n{}:1:I'm a hidden file created to test grep!\n--", file(".hidden_text.txt"))));
     // sample_text2.txt found
-    assert!(stdout.contains("sample_text2.txt"));
-    assert!(stdout.contains("1-I'm contained into a hidden folder.\n2:I'd like to test grep"));
+    assert!(stdout.contains(&format!("{0}-1-I'm contained into a hidden folder.\n{0}:2:I'd like to test grep", file(".hidden_folder/sample_text2.txt"))));
 
     // Invalid command combination error
     let mut cmd = Command::new(binary.clone());
-    cmd.arg("grep").arg("grep").arg(path.to_str().unwrap()).arg("-R").arg("-H").arg("-C").arg("1").arg("-n").arg("-c");
-    cmd.assert().success(); // Note that even in error, we're handling them to show a nice output message, then the command is successfully executed
-    cmd.assert().stdout("Introduced an invalid combination of commands in grep mode. For more information try --help.\n");
+    cmd.arg("grep").arg("grep").arg(path.to_str().unwrap()).arg("-R").arg("--hidden-items").arg("-C").arg("1").arg("-n").arg("-c");
+    cmd.assert().code(2); // Errors exit with 2, and their message goes to the standard error naming the conflicting flags
+    let stderr = String::from_utf8(cmd.assert().get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("the argument '--line-number' cannot be used with '--count'"));
+
+    // Empty research error
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("Grep").arg(path.to_str().unwrap()).arg("-R").arg("--hidden-items").arg("-C").arg("1").arg("-n");
+    cmd.assert().code(1); // An empty research exits with 1, silently as in grep
+    cmd.assert().stdout("");
+    cmd.assert().stderr("");
+
+}
+
+#[test]
+// If no path is given, or the path is -, grep reads from the standard input. Let's test:
+// 1. A search reading from the standard input without path.
+// 2. A search reading from the standard input through - together with a file.
+// 3. An empty research from the standard input.
+fn grep_stdin_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test file
+    let mut path = PathBuf::new();
+    path.push(".");
+    path.push("tests");
+    path.push("grep_files");
+    path.push("sample_text.txt");
+
+    // The standard output'll be here
+    let mut stdout;
+
+    // Search in the standard input without path
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg("-n").arg("-A").arg("1").write_stdin("Hey you\nI'm grep from a pipe\nBye");
+    cmd.assert().success();
+    unsafe {stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout)} //The output is for sure a valid String
+    assert_eq!("2:I'm grep from a pipe\n3-Bye\n", stdout); // A single input, so its name isn't shown
+
+    // Search in the standard input and in a file at the same time
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg("-").arg(path.to_str().unwrap()).arg("-l").write_stdin("I'm grep from a pipe");
+    cmd.assert().success();
+    unsafe {stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout)}
+    assert!(stdout.contains("(standard input)"));
+    assert!(stdout.contains("sample_text.txt"));
 
     // Empty research error
     let mut cmd = Command::new(binary.clone());
-    cmd.arg("grep").arg("Grep").arg(path.to_str().unwrap()).arg("-R").arg("-H").arg("-C").arg("1").arg("-n");
+    cmd.arg("grep").arg("Grep").arg("-").write_stdin("I'm grep from a pipe");
+    cmd.assert().code(1); // An empty research exits with 1, silently as in grep
+    cmd.assert().stdout("");
+    cmd.assert().stderr("");
+}
+
+
+#[test]
+// Binary files are detected and reported instead of making grep fail. Let's test:
+// 1. The default behavior, reporting that the binary file matches.
+// 2. The binary file is skipped with --binary-files=without-match.
+// 3. The binary file is searched as text with -a.
+fn grep_binary_files_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test folder
+    let mut path = PathBuf::new();
+    path.push(".");
+    path.push("tests");
+    path.push("grep_files");
+
+    // The standard output'll be here
+    let mut stdout;
+
+    // The binary file is reported
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg(path.to_str().unwrap()).arg("-R");
+    cmd.assert().success();
+    unsafe {stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout)} //The output is for sure a valid String
+    assert!(stdout.contains("Binary file"));
+    assert!(stdout.contains("binary_file.bin matches"));
+    assert!(stdout.contains("I'm grep")); // The text files are still searched
+
+    // The binary file is skipped
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg(path.to_str().unwrap()).arg("-R").arg("--binary-files=without-match");
+    cmd.assert().success();
+    unsafe {stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout)}
+    assert!(!stdout.contains("binary_file.bin"));
+    assert!(stdout.contains("I'm grep"));
+
+    // The binary file is searched as text
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("second").arg(path.to_str().unwrap()).arg("-R").arg("-a");
+    cmd.assert().success();
+    unsafe {stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout)}
+    assert!(stdout.contains("binary_file.bin"));
+    assert!(stdout.contains("second line with grep"));
+}
+
+
+#[test]
+// A file that cannot be read doesn't stop grep. Let's test:
+// 1. The error is reported in the standard error, the results of the other files are printed and the exit status reflects the error.
+// 2. The error message is silenced with -s, but the exit status still reflects the error.
+// 3. As several files were named, the lines of the readable one keep its name even if the other one is dropped.
+fn grep_errors_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test files
+    let mut path = PathBuf::new();
+    path.push(".");
+    path.push("tests");
+    path.push("grep_files");
+    let mut unexistent = path.clone();
+    path.push("sample_text.txt");
+    unexistent.push("unexistent.txt");
+
+    // The standard output and error'll be here
+    let mut stdout;
+    let stderr;
+
+    // The error is reported
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg(unexistent.to_str().unwrap()).arg(path.to_str().unwrap());
+    cmd.assert().code(2);
+    unsafe {stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout)} //The output is for sure a valid String
+    unsafe {stderr = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stderr)}
+    assert!(stdout.contains("I'm grep"));
+    assert!(stderr.contains("unexistent.txt' doesn't match any file or directory"));
+
+    // The error is silenced
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg(unexistent.to_str().unwrap()).arg(path.to_str().unwrap()).arg("-s");
+    cmd.assert().code(2);
+    unsafe {stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout)}
+    assert!(stdout.contains("I'm grep"));
+    cmd.assert().stderr("");
+
+    // The file name is kept
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg(path.to_str().unwrap()).arg(unexistent.to_str().unwrap()).arg("-s");
+    cmd.assert().code(2).stdout(format!("{}:I'm grep\n", path.to_str().unwrap()));
+}
+
+
+#[test]
+// The results of several files are printed in a deterministic order, no matter which thread finishes first. Let's test:
+// 1. The traversal order is kept and it's the same across several runs.
+// 2. The results can be sorted by path.
+fn grep_order_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test folder
+    let mut path = PathBuf::new();
+    path.push(".");
+    path.push("tests");
+    path.push("grep_files");
+    let mut sample_text = path.clone();
+    sample_text.push("sample_text.txt");
+
+    // The standard output'll be here
+    let mut stdout;
+
+    // Traversal order. The sample_text.txt passed as argument goes first, then the directory contents in alphabetical order
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg(sample_text.to_str().unwrap()).arg(path.to_str().unwrap()).arg("-R").arg("--hidden-items").arg("-l");
+    cmd.assert().success();
+    unsafe {stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout)} //The output is for sure a valid String
+    let files = stdout.lines().filter(|line| !line.is_empty()).collect::<Vec<&str>>();
+    assert_eq!(5, files.len());
+    assert!(files[0].ends_with("grep_files/sample_text.txt"));
+    assert!(files[1].ends_with(".hidden_folder/sample_text2.txt"));
+    assert!(files[2].ends_with(".hidden_text.txt"));
+    assert!(files[3].ends_with("binary_file.bin"));
+    assert!(files[4].ends_with("grep_files/sample_text.txt"));
+    for _ in 0..5{ // The output is the same in every run
+        cmd.assert().success().stdout(stdout.clone());
+    }
+
+    // Sorted by path
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg(sample_text.to_str().unwrap()).arg(path.to_str().unwrap()).arg("-R").arg("--hidden-items").arg("-l").arg("--sort=path");
+    cmd.assert().success();
+    unsafe {stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout)}
+    let files = stdout.lines().filter(|line| !line.is_empty()).collect::<Vec<&str>>();
+    let mut sorted = files.clone();
+    sorted.sort();
+    assert_eq!(sorted, files);
+}
+
+
+
+#[test]
+// The paths are globs, not regular expressions, and the directory tree can be limited. Let's test:
+// 1. A path with a dot doesn't match other characters in its place.
+// 2. ** goes down in the directory tree.
+// 3. --max-depth limits how deep the tree is explored.
+fn grep_paths_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test folder
+    let mut path = PathBuf::new();
+    path.push(".");
+    path.push("tests");
+    path.push("grep_files");
+
+    // The standard output'll be here
+    let stdout;
+
+    // The dot is just a dot
+    let mut not_found = path.clone();
+    not_found.push("sample_textXtxt");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg(not_found.to_str().unwrap());
+    cmd.assert().code(2);
+
+    // ** finds the text in the hidden folder too when hidden items are requested
+    let mut recursive = path.clone();
+    recursive.push("**");
+    recursive.push("*.txt");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg(recursive.to_str().unwrap()).arg("--hidden-items").arg("-l");
+    cmd.assert().success();
+    unsafe {stdout = String::from_utf8_unchecked(cmd.assert().get_output().to_owned().stdout)} //The output is for sure a valid String
+    assert!(stdout.contains("sample_text.txt"));
+    assert!(stdout.contains("sample_text2.txt"));
+
+    // The hidden folder is deeper than the limit
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("contained into a hidden folder").arg(path.to_str().unwrap()).arg("-R").arg("--hidden-items").arg("--max-depth").arg("1");
+    cmd.assert().code(1);
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("contained into a hidden folder").arg(path.to_str().unwrap()).arg("-R").arg("--hidden-items").arg("--max-depth").arg("2");
+    cmd.assert().success();
+}
+
+
+#[test]
+// The output is written while grep is still searching, so a reader going away stops it. Let's test:
+// 1. Closing the standard output before grep finishes, as head does, stops grep quietly.
+fn grep_broken_pipe_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // The output is much bigger than the buffer of a pipe, so grep finds the pipe closed while writing it
+    let mut child = std::process::Command::new(binary)
+        .arg("grep").arg("grep")
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    drop(child.stdout.take()); // Nobody reads the output
+    let mut stdin = child.stdin.take().unwrap();
+    let _ = stdin.write_all("I'm grep\n".repeat(100000).as_bytes()); // It may fail if grep has already stopped
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!("", String::from_utf8(output.stderr).unwrap());
+}
+
+
+
+#[test]
+// The lines are written as soon as they're found, without waiting for the end of the input. Let's test:
+// 1. The first line selected from the standard input is read while the input is still open.
+// 2. The rest of the lines follow once they arrive.
+fn grep_streaming_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    let mut child = std::process::Command::new(binary)
+        .arg("grep").arg("foo")
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    stdin.write_all(b"foo\nbar\n").unwrap();
+    stdin.flush().unwrap();
+
+    // The line is read in another thread, so the test fails instead of hanging if grep waits for the end of the input
+    let (tx, rx) = mpsc::channel();
+    let reader = thread::spawn(move ||{
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        tx.send(line).unwrap();
+        stdout
+    });
+    let first = rx.recv_timeout(Duration::from_secs(10));
+    stdin.write_all(b"another foo\n").unwrap();
+    drop(stdin);
+    assert_eq!(Ok("foo\n".to_string()), first);
+
+    let mut rest = String::new();
+    reader.join().unwrap().read_to_string(&mut rest).unwrap();
+    assert_eq!("another foo\n", rest);
+    assert!(child.wait().unwrap().success());
+}
+
+
+#[test]
+// The output can be colored as in GNU grep. Let's test:
+// 1. --color=always highlights the matches, the line numbers and the separators, and --color=never doesn't.
+// 2. The colors are taken from GREP_COLORS.
+// 3. By default, nothing is colored if the output isn't a terminal, as it happens here.
+fn grep_color_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Always colored, with the default colors of GNU grep
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--color=always").arg("grep").arg("-n").arg("-H").env_remove("GREP_COLORS").write_stdin("I'm grep");
+    cmd.assert().success().stdout("\x1b[35m\x1b[K(standard input)\x1b[m\x1b[K\x1b[36m\x1b[K:\x1b[m\x1b[K\x1b[32m\x1b[K1\x1b[m\x1b[K\x1b[36m\x1b[K:\x1b[m\x1b[KI'm \x1b[01;31m\x1b[Kgrep\x1b[m\x1b[K\n");
+
+    // Custom colors
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--color=always").arg("grep").arg("-l").env("GREP_COLORS", "fn=34").write_stdin("I'm grep");
+    cmd.assert().success().stdout("\x1b[34m\x1b[K(standard input)\x1b[m\x1b[K\n");
+
+    // Never colored, and not colored by default out of a terminal
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--color=never").arg("grep").arg("-n").write_stdin("I'm grep");
+    cmd.assert().success().stdout("1:I'm grep\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--color").arg("grep").arg("-n").write_stdin("I'm grep");
+    cmd.assert().success().stdout("1:I'm grep\n");
+}
+
+
+#[test]
+// The output follows the format of GNU grep, so the tools parsing it work with ours. Let's test:
+// 1. The lines are prefixed with their file if several files are searched, unless -h is set.
+// 2. The groups of context lines are separated by --, which can be changed or removed.
+// 3. The counts are prefixed with their file.
+// 4. --help is still available even if -h is taken.
+fn grep_format_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test file
+    let mut path = PathBuf::new();
+    path.push(".");
+    path.push("tests");
+    path.push("grep_files");
+    path.push("sample_text.txt");
+    let path = path.to_str().unwrap();
+
+    // Several files, with and without their names
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg("-").arg(path).arg("-n").write_stdin("I'm grep from a pipe");
+    cmd.assert().success().stdout(format!("(standard input):1:I'm grep from a pipe\n{}:3:I'm grep\n", path));
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg("-").arg(path).arg("-h").write_stdin("I'm grep from a pipe");
+    cmd.assert().success().stdout("I'm grep from a pipe\nI'm grep\n");
+
+    // Group separators
+    let contents = "grep\none\ntwo\nthree\ngrep";
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg("-A").arg("1").write_stdin(contents);
+    cmd.assert().success().stdout("grep\none\n--\ngrep\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg("-A").arg("1").arg("--group-separator").arg("***").write_stdin(contents);
+    cmd.assert().success().stdout("grep\none\n***\ngrep\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg("-A").arg("1").arg("--no-group-separator").write_stdin(contents);
+    cmd.assert().success().stdout("grep\none\ngrep\n");
+    // Without context, there's no group to separate
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").write_stdin(contents);
+    cmd.assert().success().stdout("grep\ngrep\n");
+
+    // Counts
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg("-").arg(path).arg("-c").write_stdin(contents);
+    cmd.assert().success().stdout(format!("(standard input):2\n{}:1\n", path));
+
+    // Help
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--help");
     cmd.assert().success();
-    cmd.assert().stdout("The request didn't produce any output.\n");
+}
+
+
+#[test]
+// The results can be written as JSON Lines to be read by other programs. Let's test:
+// 1. The begin, match, context and end objects of a file, followed by the summary.
+// 2. A search without results still writes the summary, but it isn't successful.
+// 3. --json cannot be used together with the counters.
+fn grep_json_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // The summary includes the time elapsed, so only its statistics are checked
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg("--json").arg("-A").arg("1").write_stdin("grep\none\ntwo\ngrep grep");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(6, lines.len());
+    assert_eq!(r#"{"type":"begin","data":{"path":{"text":"(standard input)"}}}"#, lines[0]);
+    assert_eq!(r#"{"type":"match","data":{"path":{"text":"(standard input)"},"lines":{"text":"grep"},"line_number":1,"absolute_offset":0,"submatches":[{"match":{"text":"grep"},"start":0,"end":4}]}}"#, lines[1]);
+    assert_eq!(r#"{"type":"context","data":{"path":{"text":"(standard input)"},"lines":{"text":"one"},"line_number":2,"absolute_offset":5,"submatches":[]}}"#, lines[2]);
+    assert_eq!(r#"{"type":"match","data":{"path":{"text":"(standard input)"},"lines":{"text":"grep grep"},"line_number":4,"absolute_offset":13,"submatches":[{"match":{"text":"grep"},"start":0,"end":4},{"match":{"text":"grep"},"start":5,"end":9}]}}"#, lines[3]);
+    assert_eq!(r#"{"type":"end","data":{"path":{"text":"(standard input)"},"stats":{"matched_lines":2,"matches":3,"bytes_searched":22}}}"#, lines[4]);
+    assert!(lines[5].starts_with(r#"{"type":"summary","data":{"elapsed_total":"#));
+    assert!(lines[5].ends_with(r#""stats":{"searches":1,"searches_with_match":1,"matched_lines":2,"matches":3,"bytes_searched":22}}}"#));
+
+    // Nothing found
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg("--json").write_stdin("Hey you");
+    let output = cmd.assert().code(1).get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(1, output.lines().count());
+    assert!(output.ends_with("\"stats\":{\"searches\":1,\"searches_with_match\":0,\"matched_lines\":0,\"matches\":0,\"bytes_searched\":7}}}\n"));
+
+    // Not allowed with the counters
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("grep").arg("--json").arg("-c").write_stdin("grep");
+    cmd.assert().code(2).stdout("");
+}
+
+
+#[test]
+// Several patterns can be looked for at once. Let's test:
+// 1. Repeating -e, where the positional arguments are all paths.
+// 2. Reading the patterns from a file with -f, combined with -e.
+// 3. A pattern file that cannot be read and a missing pattern.
+fn grep_patterns_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test file
+    let mut path = PathBuf::new();
+    path.push(".");
+    path.push("tests");
+    path.push("grep_files");
+    path.push("sample_text.txt");
+    let path = path.to_str().unwrap();
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-e").arg("grep").arg("-e").arg("^Nice").arg(path).arg("-n");
+    cmd.assert().success().stdout("3:I'm grep\n4:Nice to meet you\n");
+    // Without a path, the standard input is read
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-e").arg("grep").arg("-e").arg("pipe").arg("-o").write_stdin("I'm grep from a pipe");
+    cmd.assert().success().stdout("grep\npipe\n");
+
+    // The patterns of the file are added to the ones of -e
+    let patterns = env::temp_dir().join("amazing_clu_grep_patterns_test.txt");
+    std::fs::write(&patterns, "Hey\nWho\n").unwrap();
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-f").arg(&patterns).arg("-e").arg("grep").arg(path);
+    cmd.assert().success().stdout("Hey you\nHow are you? Who are you?\nI'm grep\n");
+    // An empty file of patterns doesn't match anything
+    std::fs::write(&patterns, "").unwrap();
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-f").arg(&patterns).arg(path);
+    cmd.assert().code(1).stdout("");
+    std::fs::remove_file(&patterns).unwrap();
+
+    // Errors
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-f").arg("missing_patterns.txt").arg(path);
+    cmd.assert().code(2).stdout("").stderr("Input error. missing_patterns.txt cannot be read.\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep");
+    cmd.assert().code(2).stdout("");
+}
+
+
+#[test]
+// The patterns can be read in several syntaxes. Let's test:
+// 1. -F takes the patterns as fixed strings.
+// 2. -w and -x only select whole words and whole lines.
+// 3. -G reads POSIX basic regular expressions, while -E keeps the default syntax.
+// 4. The syntaxes cannot be mixed.
+fn grep_matchers_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    let contents = "a.b()\naxb()\ngrep\nagrep\ngrep(1)\ngrep1";
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-F").arg("a.b()").write_stdin(contents);
+    cmd.assert().success().stdout("a.b()\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("a.b()").write_stdin(contents);
+    cmd.assert().success().stdout("a.b()\naxb()\n");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-w").arg("grep").write_stdin(contents);
+    cmd.assert().success().stdout("grep\ngrep(1)\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-x").arg("-e").arg("grep").arg("-e").arg("a.*").write_stdin(contents);
+    cmd.assert().success().stdout("a.b()\naxb()\ngrep\nagrep\n");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-G").arg("grep(1)").write_stdin(contents);
+    cmd.assert().success().stdout("grep(1)\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-G").arg(r"^\(a\|g\)rep$").write_stdin(contents);
+    cmd.assert().success().stdout("grep\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-E").arg("^(a|g)rep$").write_stdin(contents);
+    cmd.assert().success().stdout("grep\n");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-F").arg("-G").arg("grep").write_stdin(contents);
+    cmd.assert().code(2).stdout("");
+}
+
+
+#[test]
+// The case of the patterns can be ignored in several ways. Let's test:
+// 1. -S ignores the case only if the patterns are lowercase.
+// 2. --no-ignore-case overrides -i and -S, and the last of them wins.
+// 3. --case-folding ascii only folds the ASCII letters.
+fn grep_case_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    let contents = "Grep\ngrep\nécole\nÉCOLE\néCOLE";
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-S").arg("grep").write_stdin(contents);
+    cmd.assert().success().stdout("Grep\ngrep\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-S").arg("Grep").write_stdin(contents);
+    cmd.assert().success().stdout("Grep\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-S").arg("-e").arg("grep").arg("-e").arg("École").write_stdin(contents);
+    cmd.assert().success().stdout("grep\n");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-i").arg("--no-ignore-case").arg("grep").write_stdin(contents);
+    cmd.assert().success().stdout("grep\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--no-ignore-case").arg("-i").arg("grep").write_stdin(contents);
+    cmd.assert().success().stdout("Grep\ngrep\n");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-i").arg("école").write_stdin(contents);
+    cmd.assert().success().stdout("école\nÉCOLE\néCOLE\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-i").arg("--case-folding").arg("ascii").arg("école").write_stdin(contents);
+    cmd.assert().success().stdout("école\néCOLE\n");
+}
+
+
+#[test]
+// The patterns can match across lines in multiline mode. Let's test:
+// 1. Every line covered by a match is selected, with its line number and context.
+// 2. --multiline-dotall lets . match the line terminators, and it requires -U.
+// 3. The counters count the lines covered and the matches.
+// 4. -l and -L with -v list the same files with and without -U.
+fn grep_multiline_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    let contents = "fn main(\n    x,\n) {\n}\nfn other(x)";
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-U").arg("-n").arg("-A").arg("1").arg(r"fn \w+\(\n\s*x").write_stdin(contents);
+    cmd.assert().success().stdout("1:fn main(\n2:    x,\n3-) {\n");
+    // Without -U, the lines are matched one by one
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg(r"fn \w+\(\n\s*x").write_stdin(contents);
+    cmd.assert().code(1).stdout("");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-U").arg("-o").arg(r"\(\n\s*x").write_stdin(contents);
+    cmd.assert().success().stdout("(\n    x\n");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-U").arg("--multiline-dotall").arg("-n").arg(r"\{.*\}").write_stdin(contents);
+    cmd.assert().success().stdout("3:) {\n4:}\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-U").arg("-n").arg(r"\{.*\}").write_stdin(contents);
+    cmd.assert().code(1).stdout("");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--multiline-dotall").arg(r"\{.*\}").write_stdin(contents);
+    cmd.assert().code(2).stdout("");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-U").arg("-c").arg(r"\(\n?\s*x").write_stdin(contents);
+    cmd.assert().success().stdout("3\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-U").arg("--total-count").arg(r"\(\n?\s*x").write_stdin(contents);
+    cmd.assert().success().stdout("2\n");
 
+    // A file matches with -v if the pattern isn't found in it, no matter if some of its lines are left uncovered
+    let matching = env::temp_dir().join("amazing_clu_grep_multiline_test_a.txt");
+    let other = env::temp_dir().join("amazing_clu_grep_multiline_test_b.txt");
+    std::fs::write(&matching, "foo\nbar\nbaz foo\nqux\n").unwrap();
+    std::fs::write(&other, "nothing\n").unwrap();
+    let (matching, other) = (matching.to_str().unwrap(), other.to_str().unwrap());
+    for multiline in [false, true]{
+        let mut cmd = Command::new(binary.clone());
+        cmd.arg("grep").arg("-l").arg("-v").arg("foo").arg(matching).arg(other);
+        if multiline{ cmd.arg("-U"); }
+        cmd.assert().success().stdout(format!("{other}\n"));
+        let mut cmd = Command::new(binary.clone());
+        cmd.arg("grep").arg("-L").arg("-v").arg("foo").arg(matching).arg(other);
+        if multiline{ cmd.arg("-U"); }
+        cmd.assert().success().stdout(format!("{matching}\n"));
+    }
+    std::fs::remove_file(matching).unwrap();
+    std::fs::remove_file(other).unwrap();
+}
+
+
+#[test]
+// The matches can be replaced by a template using the capture groups. Let's test:
+// 1. --replace replaces the matches in the printed lines, and with -o only the replacements are printed. -r is the recursive flag instead.
+// 2. --in-place writes the changed files with a backup, and --dry-run prints a diff instead.
+// 3. --in-place requires -r and can't edit the standard input.
+fn grep_replace_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    let contents = "key=value\nother line\nname=clu\n";
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--replace").arg("${val} <- $1").arg(r"(\w+)=(?P<val>\w+)").write_stdin(contents);
+    cmd.assert().success().stdout("value <- key\nclu <- name\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-o").arg("--replace").arg("[$2]").arg(r"(\w+)=(\w+)").write_stdin(contents);
+    cmd.assert().success().stdout("[value]\n[clu]\n");
+    // -r searches recursively as in GNU grep, it isn't a short --replace
+    let mut path = PathBuf::new();
+    path.push(".");
+    path.push("tests");
+    path.push("grep_files");
+    path.push("sample_text.txt");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-r").arg("Nice").arg(path.parent().unwrap());
+    cmd.assert().success().stdout(format!("{}:Nice to meet you\n", path.to_str().unwrap()));
+
+    // The file to edit is a copy, so the test can be run again
+    let file = env::temp_dir().join("amazing_clu_grep_replace_test.txt");
+    let backup = env::temp_dir().join("amazing_clu_grep_replace_test.txt.bak");
+    let path = file.to_str().unwrap();
+    std::fs::write(&file, contents).unwrap();
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--replace").arg("$2=$1").arg("--in-place").arg("--dry-run").arg(r"(\w+)=(\w+)").arg(path);
+    cmd.assert().success().stdout(format!(
+        "--- {path}\n+++ {path}\n@@ -1,1 +1,1 @@\n-key=value\n+value=key\n@@ -3,1 +3,1 @@\n-name=clu\n+clu=name\n"
+    ));
+    assert_eq!(contents, std::fs::read_to_string(&file).unwrap());
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--replace").arg("$2=$1").arg("--in-place").arg(r"(\w+)=(\w+)").arg(path);
+    cmd.assert().success().stdout(format!("{path}\n"));
+    assert_eq!("value=key\nother line\nclu=name\n", std::fs::read_to_string(&file).unwrap());
+    assert_eq!(contents, std::fs::read_to_string(&backup).unwrap());
+    // Nothing is left to change
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--replace").arg("x").arg("--in-place").arg("missing").arg(path);
+    cmd.assert().code(1).stdout("");
+    // The edits in multiline mode agree with the lines selected, so nothing is added after the final line terminator
+    std::fs::write(&file, "one\ntwo\n").unwrap();
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-U").arg("--replace").arg("# ").arg("--in-place").arg("--backup-suffix").arg("").arg("^").arg(path);
+    cmd.assert().success().stdout(format!("{path}\n"));
+    assert_eq!("# one\n# two\n", std::fs::read_to_string(&file).unwrap());
+    std::fs::remove_file(file).unwrap();
+    std::fs::remove_file(backup).unwrap();
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--in-place").arg("key").write_stdin(contents);
+    cmd.assert().code(2).stdout("");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--replace").arg("x").arg("--in-place").arg("key").write_stdin(contents);
+    cmd.assert().code(2).stdout("");
+}
+
+
+#[test]
+// The search can be stopped before reading every line. Let's test:
+// 1. -m stops reading every file after some selected lines, but their after context is still shown.
+// 2. --max-total stops the whole search after some selected lines, taking the files in order.
+// 3. -q writes nothing and exits with 0 at the first selected line, even if some path cannot be read, or with 1 if there isn't any.
+fn grep_limits_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test file
+    let mut path = PathBuf::new();
+    path.push(".");
+    path.push("tests");
+    path.push("grep_files");
+    path.push("sample_text.txt");
+    let path = path.to_str().unwrap();
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-m").arg("1").arg("-n").arg("-A").arg("1").arg("you$").arg(path);
+    cmd.assert().success().stdout("1:Hey you\n2-How are you? Who are you?\n");
+    // The after context ends at the next line that would be selected
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-m").arg("1").arg("-n").arg("-A").arg("1").arg("you").arg(path);
+    cmd.assert().success().stdout("1:Hey you\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--max-count").arg("2").arg("-c").arg("you").arg(path).arg(path);
+    cmd.assert().success().stdout(format!("{path}:2\n{path}:2\n"));
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-m").arg("0").arg("you").arg(path);
+    cmd.assert().code(1).stdout("");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--max-total").arg("4").arg("-n").arg("you").arg(path).arg(path);
+    cmd.assert().success().stdout(format!("{path}:1:Hey you\n{path}:2:How are you? Who are you?\n{path}:4:Nice to meet you\n{path}:1:Hey you\n"));
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--max-total").arg("1").arg("-l").arg("you").arg(path).arg(path);
+    cmd.assert().success().stdout(format!("{path}\n"));
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--max-total").arg("1").arg("-c").arg("you").arg(path);
+    cmd.assert().code(2).stdout("");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-q").arg("-n").arg("you").arg("missing.txt").arg(path);
+    cmd.assert().success().stdout("");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--quiet").arg("-c").arg("grep").write_stdin("Hey you\n");
+    cmd.assert().code(1).stdout("");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-q").arg("grep").arg("missing.txt");
+    cmd.assert().code(2).stdout("");
+}
+
+
+#[test]
+// The results can be located inside their files to jump straight to them from an editor. Let's test:
+// 1. -b writes the byte offset of every line, or of every match with -o.
+// 2. --column writes the column of the first match, implying -n, and the column 1 for the selected lines without a match (eg, with -v).
+// 3. --vimgrep writes every match on its own line as file:line:column:text, and it can't show context lines.
+fn grep_positions_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    let contents = "Hey you\nHow are you? Who are you?\n";
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-b").arg("you").write_stdin(contents);
+    cmd.assert().success().stdout("0:Hey you\n8:How are you? Who are you?\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--byte-offset").arg("-o").arg("-n").arg("you").write_stdin(contents);
+    cmd.assert().success().stdout("1:4:you\n2:16:you\n2:29:you\n");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--column").arg("are").write_stdin(contents);
+    cmd.assert().success().stdout("2:5:How are you? Who are you?\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--column").arg("-v").arg("-C").arg("1").arg("Hey").write_stdin(contents);
+    cmd.assert().success().stdout("1-Hey you\n2:1:How are you? Who are you?\n");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--vimgrep").arg("you").write_stdin(contents);
+    cmd.assert().success().stdout("(standard input):1:5:Hey you\n(standard input):2:9:How are you? Who are you?\n(standard input):2:22:How are you? Who are you?\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--vimgrep").arg("-A").arg("1").arg("you").write_stdin(contents);
+    cmd.assert().code(2).stdout("");
+}
+
+
+#[test]
+// The files can be listed by what they don't contain, and the counters can be combined with -v and -l. Let's test:
+// 1. -L lists the files without the pattern, and it can't be combined with -l or -c.
+// 2. -c -v counts the lines without the pattern.
+// 3. -c -l only writes the counts of the files containing the pattern, prefixed with their names.
+// 4. The counts of 0 are written, but as no line is selected the exit status is 1.
+fn grep_files_and_counts_test(){
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap(); //If unwrap fails, we won't be able to find the binary and the test fails.
+    let mut binary = PathBuf::from(manifest_dir); // We build the path to the binary in the debug mode!
+    binary.push("target");
+    binary.push("debug");
+    binary.push("amazing_clu");
+
+    // Path to the test file
+    let mut path = PathBuf::new();
+    path.push(".");
+    path.push("tests");
+    path.push("grep_files");
+    path.push("sample_text.txt");
+    let path = path.to_str().unwrap();
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-L").arg("missing").arg(path).arg("-");
+    cmd.write_stdin("missing\n").assert().success().stdout(format!("{path}\n"));
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--files-without-match").arg("you").arg(path);
+    cmd.assert().code(1).stdout("");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-L").arg("-l").arg("you").arg(path);
+    cmd.assert().code(2).stdout("");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-L").arg("-c").arg("you").arg(path);
+    cmd.assert().code(2).stdout("");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-c").arg("-v").arg("you").arg(path);
+    cmd.assert().success().stdout("1\n");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--total-count").arg("-v").arg("you").arg(path);
+    cmd.assert().code(2).stdout("");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-c").arg("zzz").arg(path);
+    cmd.assert().code(1).stdout("0\n").stderr("");
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--total-count").arg("zzz").arg(path).arg(path);
+    cmd.assert().code(1).stdout(format!("{path}:0\n{path}:0\n")).stderr("");
+
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("-c").arg("-l").arg("grep").arg(path).arg("-");
+    cmd.write_stdin("nothing here\n").assert().success().stdout(format!("{path}:1\n"));
+    let mut cmd = Command::new(binary.clone());
+    cmd.arg("grep").arg("--total-count").arg("-l").arg("you").arg(path);
+    cmd.assert().success().stdout(format!("{path}:4\n"));
 }
\ No newline at end of file